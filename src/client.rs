@@ -0,0 +1,358 @@
+use crate::codec::{CodecError, Decoder, Encoder, Message};
+use crate::{Exit, Initialize, InitializeParams, Initialized, InitializedParams, Notification, Request, ServerCapabilities, Shutdown};
+use jsonrpc_core::{serde_json, Id, Output};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The default time that a spawned server is given to exit after the `exit` notification.
+const DEFAULT_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The interval at which a spawned server is checked for having exited.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An error that occurs while communicating with a language server.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server process could not be spawned or awaited.
+    Io(io::Error),
+    /// A message could not be written or read.
+    Codec(CodecError),
+    /// Params or a result could not be converted to or from JSON.
+    Json(serde_json::Error),
+    /// The server responded to a request with an error.
+    Response(jsonrpc_core::Error),
+    /// The server closed its output before responding to a request.
+    Disconnected,
+    /// The server process exited unsuccessfully.
+    Exit(ExitStatus),
+    /// The server process did not exit in time after the `exit` notification, so it was killed.
+    ExitTimeout,
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(error) => write!(f, "io error: {}", error),
+            ClientError::Codec(error) => write!(f, "codec error: {}", error),
+            ClientError::Json(error) => write!(f, "json error: {}", error),
+            ClientError::Response(error) => {
+                write!(f, "server responded with error {}: {}", error.code.code(), error.message)
+            }
+            ClientError::Disconnected => write!(f, "server disconnected"),
+            ClientError::Exit(status) => write!(f, "server exited with {}", status),
+            ClientError::ExitTimeout => write!(f, "server did not exit in time"),
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientError::Io(error) => Some(error),
+            ClientError::Codec(error) => Some(error),
+            ClientError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(value: io::Error) -> Self {
+        ClientError::Io(value)
+    }
+}
+
+impl From<CodecError> for ClientError {
+    fn from(value: CodecError) -> Self {
+        ClientError::Codec(value)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(value: serde_json::Error) -> Self {
+        ClientError::Json(value)
+    }
+}
+
+/// A blocking client that communicates with a language server.
+#[derive(Debug)]
+pub struct Client<R, W> {
+    /// Reads messages from the server.
+    decoder: Decoder<R>,
+    /// Writes messages to the server.
+    encoder: Encoder<W>,
+    /// The id of the next request.
+    next_id: u64,
+    /// Requests and notifications received from the server while waiting for a response.
+    incoming: VecDeque<Message>,
+    /// The server process, if spawned by the client.
+    process: Option<Child>,
+    /// The time that the server process is given to exit after the `exit` notification.
+    exit_timeout: Duration,
+}
+
+impl Client<BufReader<ChildStdout>, ChildStdin> {
+    /// Spawns `command` as a server that communicates over stdio.
+    pub fn spawn(command: &mut Command) -> Result<Self, ClientError> {
+        let mut process = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let stdin = process.stdin.take().ok_or(ClientError::Disconnected)?;
+        let stdout = process.stdout.take().ok_or(ClientError::Disconnected)?;
+        let mut client = Client::new(BufReader::new(stdout), stdin);
+
+        client.process = Some(process);
+        Ok(client)
+    }
+}
+
+impl<R: BufRead, W: Write> Client<R, W> {
+    /// Creates a `Client` that reads from `reader` and writes to `writer`.
+    pub fn new(reader: R, writer: W) -> Self {
+        Client {
+            decoder: Decoder::new(reader),
+            encoder: Encoder::new(writer),
+            next_id: 0,
+            incoming: VecDeque::new(),
+            process: None,
+            exit_timeout: DEFAULT_EXIT_TIMEOUT,
+        }
+    }
+
+    /// Sets the time that a spawned server is given to exit after the `exit` notification.
+    pub fn with_exit_timeout(mut self, exit_timeout: Duration) -> Self {
+        self.exit_timeout = exit_timeout;
+        self
+    }
+
+    /// Performs the initialize handshake.
+    ///
+    /// Sends the `initialize` request and, once the server responds, the `initialized`
+    /// notification.
+    pub fn initialize(&mut self, params: InitializeParams) -> Result<ServerCapabilities, ClientError> {
+        let result = self.request::<Initialize>(&params)?;
+
+        self.notify::<Initialized>(&InitializedParams {})?;
+        Ok(result.capabilities)
+    }
+
+    /// Sends a request of type `T` and blocks until the server responds.
+    ///
+    /// Requests and notifications received from the server in the meantime are kept for
+    /// `Client::next_message`.
+    pub fn request<T: Request>(&mut self, params: &T::Params) -> Result<T::Result, ClientError> {
+        let id = Id::Num(self.next_id);
+
+        self.next_id += 1;
+        self.encoder.write(&Message::request::<T>(id.clone(), params)?)?;
+
+        loop {
+            match self.decoder.read::<Message>()? {
+                Some(Message::Response(output)) => {
+                    if *output.id() == id {
+                        return match output {
+                            Output::Success(success) => Ok(serde_json::from_value(success.result)?),
+                            Output::Failure(failure) => Err(ClientError::Response(failure.error)),
+                        };
+                    }
+                }
+                Some(message) => self.incoming.push_back(message),
+                None => return Err(ClientError::Disconnected),
+            }
+        }
+    }
+
+    /// Sends a notification of type `T`.
+    pub fn notify<T: Notification>(&mut self, params: &T::Params) -> Result<(), ClientError> {
+        self.encoder.write(&Message::notification::<T>(params)?)?;
+        Ok(())
+    }
+
+    /// Sends a response to the request from the server identified by `id`.
+    pub fn respond(&mut self, id: Id, result: jsonrpc_core::Result<serde_json::Value>) -> Result<(), ClientError> {
+        self.encoder.write(&Message::response(id, result))?;
+        Ok(())
+    }
+
+    /// Returns the next request or notification from the server.
+    ///
+    /// Returns `Option::None` if the server closed its output.
+    pub fn next_message(&mut self) -> Result<Option<Message>, ClientError> {
+        if let Some(message) = self.incoming.pop_front() {
+            return Ok(Some(message));
+        }
+
+        Ok(self.decoder.read()?)
+    }
+
+    /// Sends the `shutdown` request followed by the `exit` notification.
+    ///
+    /// If the client spawned the server, waits for the server process to exit. If either message
+    /// fails or the server process does not exit within the exit timeout, the server process is
+    /// killed.
+    pub fn shutdown(mut self) -> Result<(), ClientError> {
+        let result = self.request::<Shutdown>(&()).and_then(|()| self.notify::<Exit>(&()));
+
+        if let Err(error) = result {
+            if let Some(mut process) = self.process.take() {
+                kill(&mut process);
+            }
+
+            return Err(error);
+        }
+
+        if let Some(mut process) = self.process.take() {
+            drop(self.encoder);
+            let deadline = Instant::now() + self.exit_timeout;
+
+            let status = loop {
+                if let Some(status) = process.try_wait()? {
+                    break status;
+                }
+
+                if Instant::now() >= deadline {
+                    kill(&mut process);
+                    return Err(ClientError::ExitTimeout);
+                }
+
+                thread::sleep(EXIT_POLL_INTERVAL);
+            };
+
+            if !status.success() {
+                return Err(ClientError::Exit(status));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Kills `process` and reaps it to avoid leaving it behind.
+fn kill(process: &mut Child) {
+    let _ = process.kill();
+    let _ = process.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::Server;
+    use crate::{HoverRequest, Position, TextDocumentIdentifier, TextDocumentPositionParams};
+    use jsonrpc_core::ErrorCode;
+    use std::io::pipe;
+    use std::sync::mpsc;
+    use std::thread::{self, JoinHandle};
+
+    /// A client connected to a server running on a thread.
+    type Connection = (
+        Client<BufReader<io::PipeReader>, io::PipeWriter>,
+        JoinHandle<Result<(), crate::server::ServerError>>,
+        mpsc::Receiver<&'static str>,
+    );
+
+    /// Runs a `Server` on a thread and returns a `Client` connected to it, the thread and the
+    /// messages observed by the server.
+    fn connect() -> Connection {
+        let (client_reader, server_writer) = pipe().unwrap();
+        let (server_reader, client_writer) = pipe().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let server = thread::spawn(move || {
+            let initialized = sender.clone();
+
+            Server::new(ServerCapabilities::default().with_hover_provider(true))
+                .on_initialize(move |_| {
+                    let _ = sender.send("initialize");
+                })
+                .on_notification::<Initialized, _>(move |_, _| {
+                    let _ = initialized.send("initialized");
+                })
+                .run(BufReader::new(server_reader), server_writer)
+        });
+
+        (Client::new(BufReader::new(client_reader), client_writer), server, receiver)
+    }
+
+    #[test]
+    fn lifecycle_is_ordered() {
+        let (mut client, server, observed) = connect();
+        let capabilities = client.initialize(InitializeParams::default()).unwrap();
+
+        assert!(capabilities.supports_hover());
+
+        client.shutdown().unwrap();
+
+        // The server fails if `exit` is received before `shutdown`.
+        server.join().unwrap().unwrap();
+        assert_eq!(observed.try_iter().collect::<Vec<_>>(), ["initialize", "initialized"]);
+    }
+
+    #[test]
+    fn error_response() {
+        let (mut client, server, _) = connect();
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::default(),
+            position: Position::default(),
+        };
+
+        match client.request::<HoverRequest>(&params) {
+            Err(ClientError::Response(error)) => {
+                assert_eq!(error.code, ErrorCode::ServerError(crate::server::SERVER_NOT_INITIALIZED))
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        client.initialize(InitializeParams::default()).unwrap();
+
+        match client.request::<HoverRequest>(&params) {
+            Err(ClientError::Response(error)) => assert_eq!(error.code, ErrorCode::MethodNotFound),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        client.shutdown().unwrap();
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn unit_params_are_omitted() {
+        let mut written = Vec::new();
+        let mut client = Client::new(&b""[..], &mut written);
+
+        client.notify::<Exit>(&()).unwrap();
+        drop(client);
+
+        let written = String::from_utf8(written).unwrap();
+
+        assert!(written.ends_with(r#"{"jsonrpc":"2.0","method":"exit"}"#), "{}", written);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_reaps_unresponsive_server() {
+        let client = Client::spawn(&mut Command::new("true")).unwrap();
+
+        match client.shutdown() {
+            Err(ClientError::Disconnected) | Err(ClientError::Codec(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_kills_server_that_ignores_exit() {
+        let body = r#"{"jsonrpc":"2.0","id":0,"result":null}"#;
+        let script = format!("printf 'Content-Length: {}\\r\\n\\r\\n{}'; exec sleep 30", body.len(), body);
+        let client = Client::spawn(Command::new("sh").args(["-c", &script]))
+            .unwrap()
+            .with_exit_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+
+        match client.shutdown() {
+            Err(ClientError::ExitTimeout) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{Notification, Request};
+use jsonrpc_core::{serde_json, Id, MethodCall, Output, Params, Value, Version};
+use serde::{de::DeserializeOwned, ser::Error as _, Deserialize, Serialize, Serializer};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// The maximum number of bytes in a single header field, including its line terminator.
+const MAX_HEADER_FIELD_LENGTH: u64 = 1024;
+/// The default maximum number of bytes in the content of a message.
+const DEFAULT_MAX_CONTENT_LENGTH: usize = 64 * 1024 * 1024;
+/// The header name for the length of the content.
+const CONTENT_LENGTH: &str = "Content-Length";
+/// The header name for the type of the content.
+const CONTENT_TYPE: &str = "Content-Type";
+
+/// An error that occurs while encoding or decoding a message.
+#[derive(Debug)]
+pub enum CodecError {
+    /// The underlying stream failed.
+    Io(io::Error),
+    /// A header field is not of the form `name: value`.
+    MalformedHeader(String),
+    /// A header field is longer than the maximum allowed length.
+    HeaderTooLong,
+    /// The header does not contain a `Content-Length` field.
+    MissingContentLength,
+    /// The value of the `Content-Length` field is not a valid length.
+    InvalidContentLength(String),
+    /// The content is longer than the maximum allowed length.
+    ContentTooLong(usize),
+    /// The `Content-Type` field specifies a charset other than utf-8.
+    UnsupportedCharset(String),
+    /// The stream ended in the middle of a message.
+    UnexpectedEof,
+    /// The content is not valid utf-8.
+    InvalidUtf8,
+    /// The content could not be converted to or from JSON.
+    Json(serde_json::Error),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(error) => write!(f, "io error: {}", error),
+            CodecError::MalformedHeader(field) => write!(f, "malformed header field `{}`", field),
+            CodecError::HeaderTooLong => write!(f, "header field exceeds {} bytes", MAX_HEADER_FIELD_LENGTH),
+            CodecError::MissingContentLength => write!(f, "header is missing `{}`", CONTENT_LENGTH),
+            CodecError::InvalidContentLength(value) => write!(f, "invalid `{}` value `{}`", CONTENT_LENGTH, value),
+            CodecError::ContentTooLong(length) => write!(f, "content length {} exceeds the maximum", length),
+            CodecError::UnsupportedCharset(charset) => write!(f, "unsupported charset `{}`", charset),
+            CodecError::UnexpectedEof => write!(f, "stream ended within a message"),
+            CodecError::InvalidUtf8 => write!(f, "content is not valid utf-8"),
+            CodecError::Json(error) => write!(f, "json error: {}", error),
+        }
+    }
+}
+
+impl Error for CodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CodecError::Io(error) => Some(error),
+            CodecError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(value: io::Error) -> Self {
+        CodecError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for CodecError {
+    fn from(value: serde_json::Error) -> Self {
+        CodecError::Json(value)
+    }
+}
+
+/// Reads messages framed by a `Content-Length` header from a stream.
+#[derive(Debug)]
+pub struct Decoder<R> {
+    /// The stream from which messages are read.
+    reader: R,
+    /// The maximum number of bytes allowed in the content of a message.
+    max_content_length: usize,
+}
+
+impl<R: Read> Decoder<BufReader<R>> {
+    /// Creates a `Decoder` that buffers `reader`.
+    pub fn from_reader(reader: R) -> Self {
+        Decoder::new(BufReader::new(reader))
+    }
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Decoder {
+            reader,
+            max_content_length: DEFAULT_MAX_CONTENT_LENGTH,
+        }
+    }
+
+    /// Sets the maximum number of bytes allowed in the content of a message.
+    pub fn with_max_content_length(mut self, max_content_length: usize) -> Self {
+        self.max_content_length = max_content_length;
+        self
+    }
+
+    /// Reads the content of the next message.
+    ///
+    /// Returns `Option::None` if the stream ended before the start of a message.
+    pub fn read_content(&mut self) -> Result<Option<String>, CodecError> {
+        let mut content_length = None;
+        let mut is_first_field = true;
+
+        loop {
+            let mut field = Vec::new();
+            let length = (&mut self.reader)
+                .take(MAX_HEADER_FIELD_LENGTH)
+                .read_until(b'\n', &mut field)?;
+
+            if length == 0 {
+                if is_first_field {
+                    return Ok(None);
+                }
+
+                return Err(CodecError::UnexpectedEof);
+            }
+
+            if !field.ends_with(b"\r\n") {
+                if length as u64 == MAX_HEADER_FIELD_LENGTH {
+                    return Err(CodecError::HeaderTooLong);
+                }
+
+                return Err(CodecError::MalformedHeader(String::from_utf8_lossy(&field).into_owned()));
+            }
+
+            field.truncate(field.len() - 2);
+            is_first_field = false;
+
+            if field.is_empty() {
+                break;
+            }
+
+            let field = String::from_utf8(field).map_err(|_| CodecError::InvalidUtf8)?;
+            let (name, value) = match field.find(':') {
+                Some(index) => (field[..index].trim(), field[index + 1..].trim()),
+                None => return Err(CodecError::MalformedHeader(field)),
+            };
+
+            if name.eq_ignore_ascii_case(CONTENT_LENGTH) {
+                content_length = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| CodecError::InvalidContentLength(value.to_string()))?,
+                );
+            } else if name.eq_ignore_ascii_case(CONTENT_TYPE) {
+                validate_content_type(value)?;
+            }
+        }
+
+        let content_length = content_length.ok_or(CodecError::MissingContentLength)?;
+
+        if content_length > self.max_content_length {
+            return Err(CodecError::ContentTooLong(content_length));
+        }
+
+        let mut content = vec![0; content_length];
+        self.reader.read_exact(&mut content).map_err(|error| {
+            if error.kind() == io::ErrorKind::UnexpectedEof {
+                CodecError::UnexpectedEof
+            } else {
+                CodecError::Io(error)
+            }
+        })?;
+
+        String::from_utf8(content).map(Some).map_err(|_| CodecError::InvalidUtf8)
+    }
+
+    /// Reads the next message and deserializes its content.
+    ///
+    /// Returns `Option::None` if the stream ended before the start of a message.
+    pub fn read<T: DeserializeOwned>(&mut self) -> Result<Option<T>, CodecError> {
+        match self.read_content()? {
+            Some(content) => Ok(Some(serde_json::from_str(&content)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Checks that the charset of a `Content-Type` value is utf-8.
+///
+/// For backwards compatibility, `utf8` is treated as `utf-8`.
+fn validate_content_type(value: &str) -> Result<(), CodecError> {
+    for parameter in value.split(';').skip(1) {
+        let mut pair = parameter.splitn(2, '=');
+        let name = pair.next().unwrap_or_default().trim();
+
+        if name.eq_ignore_ascii_case("charset") {
+            let charset = pair.next().unwrap_or_default().trim().trim_matches('"');
+
+            if !charset.eq_ignore_ascii_case("utf-8") && !charset.eq_ignore_ascii_case("utf8") {
+                return Err(CodecError::UnsupportedCharset(charset.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes messages framed by a `Content-Length` header to a stream.
+#[derive(Debug)]
+pub struct Encoder<W> {
+    /// The stream to which messages are written.
+    writer: W,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W) -> Self {
+        Encoder { writer }
+    }
+
+    /// Writes `content` as a message and flushes the stream.
+    pub fn write_content(&mut self, content: &str) -> Result<(), CodecError> {
+        write!(self.writer, "{}: {}\r\n\r\n{}", CONTENT_LENGTH, content.len(), content)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Serializes `message` and writes it as a message.
+    pub fn write<T: Serialize>(&mut self, message: &T) -> Result<(), CodecError> {
+        self.write_content(&serde_json::to_string(message)?)
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A JSON-RPC message.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+    /// A request, which expects a response.
+    Request(MethodCall),
+    /// A notification, which does not expect a response.
+    Notification(jsonrpc_core::Notification),
+    /// A response to a request.
+    Response(Output),
+}
+
+impl Message {
+    /// Creates a request message of type `T`.
+    pub fn request<T: Request>(id: Id, params: &T::Params) -> Result<Self, serde_json::Error> {
+        Ok(Message::Request(MethodCall {
+            jsonrpc: Some(Version::V2),
+            method: T::METHOD.to_string(),
+            params: to_params(params)?,
+            id,
+        }))
+    }
+
+    /// Creates a notification message of type `T`.
+    pub fn notification<T: Notification>(params: &T::Params) -> Result<Self, serde_json::Error> {
+        Ok(Message::Notification(jsonrpc_core::Notification {
+            jsonrpc: Some(Version::V2),
+            method: T::METHOD.to_string(),
+            params: to_params(params)?,
+        }))
+    }
+
+    /// Creates a response message to the request identified by `id`.
+    pub fn response(id: Id, result: jsonrpc_core::Result<Value>) -> Self {
+        Message::Response(Output::from(result, id, Some(Version::V2)))
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = match self {
+            Message::Request(method_call) => serde_json::to_value(method_call),
+            Message::Notification(notification) => serde_json::to_value(notification),
+            Message::Response(output) => serde_json::to_value(output),
+        }
+        .map_err(S::Error::custom)?;
+
+        // JSON-RPC allows params to be omitted but not to be null.
+        if let Value::Object(map) = &mut value {
+            if map.get("params") == Some(&Value::Null) {
+                map.remove("params");
+            }
+        }
+
+        value.serialize(serializer)
+    }
+}
+
+/// Converts `params` into the `Params` of a JSON-RPC message.
+fn to_params<T: Serialize>(params: &T) -> Result<Params, serde_json::Error> {
+    match serde_json::to_value(params)? {
+        Value::Null => Ok(Params::None),
+        Value::Array(values) => Ok(Params::Array(values)),
+        Value::Object(map) => Ok(Params::Map(map)),
+        _ => Err(serde_json::Error::custom("params must be an object or array")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DidChangeTextDocument, DidChangeTextDocumentParams, Initialize, InitializeParams};
+    use jsonrpc_core::serde_json::json;
+
+    fn read_content(input: &[u8]) -> Result<Option<String>, CodecError> {
+        Decoder::new(input).read_content()
+    }
+
+    /// Returns the params of `message` after it is encoded and decoded.
+    fn round_trip<T: DeserializeOwned + Serialize>(message: Message) -> T {
+        let mut encoder = Encoder::new(Vec::new());
+
+        encoder.write(&message).unwrap();
+
+        let output = encoder.into_inner();
+        let params = match Decoder::new(&output[..]).read::<Message>().unwrap().unwrap() {
+            Message::Request(method_call) => method_call.params,
+            Message::Notification(notification) => notification.params,
+            Message::Response(_) => panic!("decoded a response"),
+        };
+
+        params.parse().unwrap()
+    }
+
+    #[test]
+    fn content() {
+        let input = b"Content-Length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf8\r\n\r\n{}";
+
+        assert_eq!(read_content(input).unwrap(), Some("{}".to_string()));
+        assert_eq!(read_content(b"").unwrap(), None);
+    }
+
+    #[test]
+    fn malformed_header() {
+        assert!(matches!(read_content(b"Content-Length 2\r\n\r\n{}"), Err(CodecError::MalformedHeader(_))));
+        assert!(matches!(read_content(b"Content-Length: 2\n\n{}"), Err(CodecError::MalformedHeader(_))));
+    }
+
+    #[test]
+    fn header_too_long() {
+        let mut input = format!("X-Padding: {}\r\n", "a".repeat(MAX_HEADER_FIELD_LENGTH as usize)).into_bytes();
+
+        input.extend_from_slice(b"Content-Length: 2\r\n\r\n{}");
+        assert!(matches!(read_content(&input), Err(CodecError::HeaderTooLong)));
+    }
+
+    #[test]
+    fn missing_content_length() {
+        assert!(matches!(
+            read_content(b"Content-Type: application/vscode-jsonrpc\r\n\r\n{}"),
+            Err(CodecError::MissingContentLength)
+        ));
+    }
+
+    #[test]
+    fn invalid_content_length() {
+        assert!(matches!(
+            read_content(b"Content-Length: -2\r\n\r\n{}"),
+            Err(CodecError::InvalidContentLength(value)) if value == "-2"
+        ));
+    }
+
+    #[test]
+    fn content_too_long() {
+        let result = Decoder::new(&b"Content-Length: 2\r\n\r\n{}"[..]).with_max_content_length(1).read_content();
+
+        assert!(matches!(result, Err(CodecError::ContentTooLong(2))));
+    }
+
+    #[test]
+    fn unsupported_charset() {
+        assert!(matches!(
+            read_content(b"Content-Length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=latin1\r\n\r\n{}"),
+            Err(CodecError::UnsupportedCharset(charset)) if charset == "latin1"
+        ));
+    }
+
+    #[test]
+    fn unexpected_eof() {
+        assert!(matches!(read_content(b"Content-Length: 2\r\n"), Err(CodecError::UnexpectedEof)));
+        assert!(matches!(read_content(b"Content-Length: 3\r\n\r\n{}"), Err(CodecError::UnexpectedEof)));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert!(matches!(read_content(b"Content-Length: 2\r\n\r\n\xff\xfe"), Err(CodecError::InvalidUtf8)));
+    }
+
+    #[test]
+    fn invalid_json() {
+        let result = Decoder::new(&b"Content-Length: 1\r\n\r\n{"[..]).read::<Value>();
+
+        assert!(matches!(result, Err(CodecError::Json(_))));
+    }
+
+    #[test]
+    fn initialize_params_round_trip() {
+        let value = json!({
+            "processId": 1,
+            "rootUri": "file:///a",
+            "capabilities": {
+                "general": {"positionEncodings": ["utf-8", "utf-16"]},
+                "textDocument": {"synchronization": {"didSave": true}},
+            },
+            "trace": "verbose",
+            "workspaceFolders": [{"uri": "file:///a", "name": "a"}],
+        });
+        let params: InitializeParams = serde_json::from_value(value).unwrap();
+        let decoded: InitializeParams = round_trip(Message::request::<Initialize>(Id::Num(1), &params).unwrap());
+
+        assert_eq!(serde_json::to_value(decoded).unwrap(), serde_json::to_value(params).unwrap());
+    }
+
+    #[test]
+    fn did_change_text_document_params_round_trip() {
+        let value = json!({
+            "textDocument": {"uri": "file:///a", "version": 2},
+            "contentChanges": [
+                {"range": {"start": {"line": 0, "character": 1}, "end": {"line": 1, "character": 0}}, "text": "é😀"},
+                {"text": "a\r\nb"},
+            ],
+        });
+        let params: DidChangeTextDocumentParams = serde_json::from_value(value.clone()).unwrap();
+        let decoded: DidChangeTextDocumentParams =
+            round_trip(Message::notification::<DidChangeTextDocument>(&params).unwrap());
+
+        assert_eq!(serde_json::to_value(decoded).unwrap(), value);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::document::TextDocument;
+use crate::{Range, TextDocumentContentChangeEvent, TextDocumentItem};
+
+/// The maximum number of inserted and deleted lines for which a line diff is computed.
+///
+/// Beyond this, the changed text is replaced by a single change.
+const MAX_EDIT_DISTANCE: usize = 1024;
+
+/// The start and end indices of a sequence of lines.
+type Span = (usize, usize);
+
+/// Returns the changes that, when applied to `old` in order, result in `new`.
+///
+/// The lines between the common prefix and suffix of the texts are diffed and each group of
+/// changed lines is trimmed to the text that differs. Changes are ordered from the end of the
+/// document to the start, so that the `Range` of each is in terms of `old` and
+/// `Position::character` is in UTF-16 code units.
+pub fn diff(old: &str, new: &str) -> Vec<TextDocumentContentChangeEvent> {
+    let (mut prefix, mut suffix) = common_affixes(old, new);
+
+    if prefix == old.len() && prefix == new.len() {
+        return Vec::new();
+    }
+
+    // Diffing whole lines keeps a changed line from being matched with part of another.
+    while !is_line_start(old, prefix) {
+        prefix -= 1;
+    }
+
+    while suffix > 0 && !(is_line_start(old, old.len() - suffix) && is_line_start(new, new.len() - suffix)) {
+        suffix -= 1;
+    }
+
+    let old_lines = lines(old, prefix, old.len() - suffix);
+    let new_lines = lines(new, prefix, new.len() - suffix);
+    let old_pieces = old_lines.windows(2).map(|w| &old[w[0]..w[1]]).collect::<Vec<_>>();
+    let new_pieces = new_lines.windows(2).map(|w| &new[w[0]..w[1]]).collect::<Vec<_>>();
+    let document = TextDocument::new(TextDocumentItem {
+        text: old.to_string(),
+        ..TextDocumentItem::default()
+    });
+
+    hunks(&old_pieces, &new_pieces)
+        .into_iter()
+        .rev()
+        .map(|(old_span, new_span)| {
+            let (old_start, old_end) = (old_lines[old_span.0], old_lines[old_span.1]);
+            let (new_start, new_end) = (new_lines[new_span.0], new_lines[new_span.1]);
+            let (prefix, suffix) = common_affixes(&old[old_start..old_end], &new[new_start..new_end]);
+            let range = Range {
+                start: document.position_at(old_start + prefix),
+                end: document.position_at(old_end - suffix),
+            };
+
+            TextDocumentContentChangeEvent::new(range, new[new_start + prefix..new_end - suffix].to_string())
+        })
+        .collect()
+}
+
+/// Returns if `offset` is the start of a line of `text`.
+fn is_line_start(text: &str, offset: usize) -> bool {
+    let bytes = text.as_bytes();
+
+    match offset.checked_sub(1).map(|index| bytes[index]) {
+        None | Some(b'\n') => true,
+        Some(b'\r') => bytes.get(offset) != Some(&b'\n'),
+        Some(_) => false,
+    }
+}
+
+/// Returns the lengths in bytes of the common prefix and suffix of `old` and `new`.
+///
+/// Neither splits a `char` or a `\r\n` line terminator, and they do not overlap.
+fn common_affixes(old: &str, new: &str) -> (usize, usize) {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let mut prefix = old_bytes.iter().zip(new_bytes).take_while(|(a, b)| a == b).count();
+
+    while !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    if prefix > 0
+        && old_bytes[prefix - 1] == b'\r'
+        && (old_bytes.get(prefix) == Some(&b'\n') || new_bytes.get(prefix) == Some(&b'\n'))
+    {
+        prefix -= 1;
+    }
+
+    let mut suffix = old_bytes[prefix..]
+        .iter()
+        .rev()
+        .zip(new_bytes[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    while !old.is_char_boundary(old.len() - suffix) {
+        suffix -= 1;
+    }
+
+    // The suffix may be all of `old` or `new`, in which case nothing precedes it.
+    let precedes_suffix = |bytes: &[u8]| bytes.len().checked_sub(suffix + 1).map(|index| bytes[index]);
+
+    if suffix > 0
+        && old_bytes[old.len() - suffix] == b'\n'
+        && (precedes_suffix(old_bytes) == Some(b'\r') || precedes_suffix(new_bytes) == Some(b'\r'))
+    {
+        suffix -= 1;
+    }
+
+    (prefix, suffix)
+}
+
+/// Returns the byte offsets that split `text[start..end]` into lines, including `start` and
+/// `end`.
+///
+/// Each line includes its terminator.
+fn lines(text: &str, start: usize, end: usize) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut offsets = vec![start];
+
+    if start == end {
+        return offsets;
+    }
+
+    for index in start..end {
+        let ends_line = match bytes[index] {
+            b'\n' => true,
+            b'\r' => bytes.get(index + 1) != Some(&b'\n'),
+            _ => false,
+        };
+
+        if ends_line && index + 1 < end {
+            offsets.push(index + 1);
+        }
+    }
+
+    offsets.push(end);
+    offsets
+}
+
+/// Returns the spans of `old` and `new` that differ, in order.
+///
+/// Uses the Myers diff algorithm, falling back to a single span if more than
+/// `MAX_EDIT_DISTANCE` lines differ.
+fn hunks(old: &[&str], new: &[&str]) -> Vec<(Span, Span)> {
+    let mut hunks = Vec::new();
+    let mut previous = (0, 0);
+
+    for (x, y) in matches(old, new).into_iter().chain(Some((old.len(), new.len()))) {
+        if x > previous.0 || y > previous.1 {
+            hunks.push(((previous.0, x), (previous.1, y)));
+        }
+
+        previous = (x + 1, y + 1);
+    }
+
+    hunks
+}
+
+/// Returns the indices of the lines of `old` and `new` that are kept, in order.
+fn matches(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_EDIT_DISTANCE) as isize;
+    // Diagonal `k` is at index `k + offset`.
+    let offset = max + 1;
+    let mut v = vec![0; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v.clone());
+
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[i] = x;
+
+            if x >= n && y >= m {
+                return backtrack(&trace, offset, n, m);
+            }
+        }
+    }
+
+    Vec::new()
+}
+
+/// Returns the kept lines on the path through `trace` that ends at `(x, y)`.
+fn backtrack(trace: &[Vec<isize>], offset: isize, mut x: isize, mut y: isize) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let i = (k + offset) as usize;
+        let previous_k = if k == -d || (k != d && v[i - 1] < v[i + 1]) { k + 1 } else { k - 1 };
+        let previous_x = v[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+
+        if d > 0 {
+            x = previous_x;
+            y = previous_y;
+        }
+    }
+
+    matches.reverse();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pieces of generated texts, which mix line endings and characters of different widths.
+    const PIECES: [&str; 7] = ["a", "b", "é", "😀", "\r", "\n", "\r\n"];
+
+    /// A xorshift generator, which keeps failures reproducible by their seed.
+    struct Generator(u64);
+
+    impl Generator {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+
+        fn pieces(&mut self, max: usize) -> Vec<&'static str> {
+            (0..self.below(max + 1)).map(|_| PIECES[self.below(PIECES.len())]).collect()
+        }
+    }
+
+    fn document(text: &str) -> TextDocument {
+        TextDocument::new(TextDocumentItem {
+            text: text.to_string(),
+            ..TextDocumentItem::default()
+        })
+    }
+
+    #[test]
+    fn changes_round_trip() {
+        for seed in 1..5000_u64 {
+            // Spreads the bits of small seeds so that the first values generated differ.
+            let mut generator = Generator(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let old = generator.pieces(24);
+            let mut new = old.clone();
+
+            for _ in 0..generator.below(4) {
+                let start = generator.below(new.len() + 1);
+                let end = start + generator.below(new.len() - start + 1);
+                let inserted = generator.pieces(4);
+
+                new.splice(start..end, inserted);
+            }
+
+            let (old, new) = (old.concat(), new.concat());
+            let expected = document(&new);
+            let mut document = document(&old);
+
+            for change in diff(&old, &new) {
+                document.apply_change(&change).unwrap();
+            }
+
+            assert_eq!(document.text(), new, "seed {}", seed);
+            assert_eq!(document.line_count(), expected.line_count(), "seed {}", seed);
+
+            for index in 0..=expected.line_count() {
+                assert_eq!(document.line(index), expected.line(index), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn identical_texts() {
+        assert!(diff("a\r\nb", "a\r\nb").is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+use crate::uri::DocumentUri;
+use crate::{
+    DidChangeTextDocumentParams, LanguageId, Position, PositionEncodingKind, Range, TextDocumentContentChangeEvent,
+    TextDocumentItem,
+};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// An error that prevents changes from being applied to a `TextDocument`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DocumentError {
+    /// The changes are for a document at a different URI.
+    UriMismatch {
+        /// URI of the document.
+        expected: DocumentUri,
+        /// URI specified by the changes.
+        actual: DocumentUri,
+    },
+    /// The version specified by the changes is not greater than the version of the document.
+    StaleVersion {
+        /// Version of the document.
+        current: i64,
+        /// Version specified by the changes.
+        received: i64,
+    },
+    /// The `Range` of a change is not within the document.
+    InvalidRange(Range),
+}
+
+impl Display for DocumentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::UriMismatch { expected, actual } => {
+                write!(f, "changes for `{}` cannot be applied to `{}`", actual, expected)
+            }
+            DocumentError::StaleVersion { current, received } => {
+                write!(f, "version {} is not newer than version {}", received, current)
+            }
+            DocumentError::InvalidRange(range) => write!(
+                f,
+                "range {}:{}-{}:{} is not within the document",
+                range.start.line, range.start.character, range.end.line, range.end.character
+            ),
+        }
+    }
+}
+
+impl Error for DocumentError {}
+
+/// A text document that is kept in sync with the changes sent by the client.
+///
+/// Line starts are indexed so that line lookup and conversion between byte offsets and
+/// `Position`s do not scan the whole text.
+#[derive(Clone, Debug)]
+pub struct TextDocument {
+    /// URI of the document.
+    uri: DocumentUri,
+    /// Language identifier of the document.
+    language_id: LanguageId,
+    /// Version number of the document.
+    version: i64,
+    /// Content of the document.
+    text: String,
+    /// Byte offset of the start of each line.
+    line_starts: Vec<usize>,
+    /// Code units counted by `Position::character`.
+    encoding: PositionEncodingKind,
+}
+
+impl TextDocument {
+    pub fn new(item: TextDocumentItem) -> Self {
+        let line_starts = line_starts(&item.text, 0, item.text.len());
+
+        TextDocument {
+            uri: item.uri,
+            language_id: item.language_id,
+            version: item.version,
+            text: item.text,
+            line_starts,
+            encoding: PositionEncodingKind::Utf16,
+        }
+    }
+
+    /// Sets the code units counted by `Position::character`.
+    pub fn with_encoding(mut self, encoding: PositionEncodingKind) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the URI of the document.
+    pub fn uri(&self) -> &DocumentUri {
+        &self.uri
+    }
+
+    /// Returns the language identifier of the document.
+    pub fn language_id(&self) -> &LanguageId {
+        &self.language_id
+    }
+
+    /// Returns the version number of the document.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the content of the document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of lines in the document.
+    ///
+    /// A document that ends with a line terminator has an empty last line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the content of the line at `index`, excluding its terminator.
+    pub fn line(&self, index: usize) -> Option<&str> {
+        let start = *self.line_starts.get(index)?;
+
+        Some(&self.text[start..self.line_end(index)])
+    }
+
+    /// Returns the byte offset of `position`.
+    ///
+    /// `Position::character` is in the code units of the encoding of the document and defaults to
+    /// the line length if greater. Returns `Option::None` if `Position::line` is not within the
+    /// document.
+    pub fn offset_at(&self, position: Position) -> Option<usize> {
+        let index = usize::try_from(position.line).ok()?;
+        let line = self.line(index)?;
+
+        Some(self.line_starts[index] + self.encoding.byte_offset(line, position.character))
+    }
+
+    /// Returns the `Position` of the byte offset `offset`.
+    ///
+    /// An offset past the end of the document is treated as the end of the document, an offset
+    /// within a line terminator is treated as the end of its line, and an offset within a `char`
+    /// is rounded down to the start of the `char`.
+    pub fn position_at(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        // The first line starts at 0, so the partition point is at least 1.
+        let index = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[index];
+        let line = &self.text[start..self.line_end(index)];
+
+        Position {
+            line: index as u64,
+            character: self.encoding.character_offset(line, offset - start),
+        }
+    }
+
+    /// Applies the content changes of `params` in order and updates the version.
+    ///
+    /// If any change cannot be applied, the document is left unchanged.
+    pub fn apply_changes(&mut self, params: &DidChangeTextDocumentParams) -> Result<(), DocumentError> {
+        let text_document = params.text_document();
+
+        if text_document.uri != self.uri {
+            return Err(DocumentError::UriMismatch {
+                expected: self.uri.clone(),
+                actual: text_document.uri.clone(),
+            });
+        }
+
+        if let Some(version) = text_document.version {
+            if version <= self.version {
+                return Err(DocumentError::StaleVersion {
+                    current: self.version,
+                    received: version,
+                });
+            }
+        }
+
+        let text = self.text.clone();
+        let line_starts = self.line_starts.clone();
+
+        for change in params.content_changes() {
+            if let Err(error) = self.apply_change(change) {
+                self.text = text;
+                self.line_starts = line_starts;
+                return Err(error);
+            }
+        }
+
+        if let Some(version) = text_document.version {
+            self.version = version;
+        }
+
+        Ok(())
+    }
+
+    /// Applies `change` without updating the version.
+    pub fn apply_change(&mut self, change: &TextDocumentContentChangeEvent) -> Result<(), DocumentError> {
+        let range = match change.range() {
+            Some(range) => *range,
+            None => {
+                self.text = change.text().to_string();
+                self.line_starts = line_starts(&self.text, 0, self.text.len());
+                return Ok(());
+            }
+        };
+        let (start, end) = match (self.offset_at(range.start), self.offset_at(range.end)) {
+            (Some(start), Some(end)) if start <= end => (start, end),
+            _ => return Err(DocumentError::InvalidRange(range)),
+        };
+
+        self.text.replace_range(start..end, change.text());
+
+        // Offsets are clamped to the content of a line, so a change never splits a line
+        // terminator and only the line starts within the inserted text need to be found. A
+        // change at the start of a line may join a preceding `\r` with a following `\n`, so the
+        // preceding line is scanned as well.
+        let mut first = range.start.line as usize;
+
+        if first > 0 && self.text.as_bytes()[self.line_starts[first] - 1] == b'\r' {
+            first -= 1;
+        }
+
+        let last = range.end.line as usize;
+        let inserted_end = start + change.text().len();
+        let shifted = self.line_starts[last + 1..]
+            .iter()
+            .map(|line_start| line_start + change.text().len() - (end - start))
+            .collect::<Vec<_>>();
+        let starts = line_starts(&self.text, self.line_starts[first], inserted_end);
+
+        self.line_starts.truncate(first);
+        self.line_starts.extend(starts);
+        self.line_starts.extend(shifted);
+        Ok(())
+    }
+
+    /// Returns the byte offset of the end of the content of the line at `index`.
+    fn line_end(&self, index: usize) -> usize {
+        let end = self.line_starts.get(index + 1).copied().unwrap_or(self.text.len());
+        let line = &self.text[self.line_starts[index]..end];
+
+        self.line_starts[index] + line.trim_end_matches(['\n', '\r']).len()
+    }
+}
+
+impl From<TextDocumentItem> for TextDocument {
+    fn from(value: TextDocumentItem) -> Self {
+        TextDocument::new(value)
+    }
+}
+
+impl From<TextDocument> for TextDocumentItem {
+    fn from(value: TextDocument) -> Self {
+        TextDocumentItem {
+            uri: value.uri,
+            language_id: value.language_id,
+            version: value.version,
+            text: value.text,
+        }
+    }
+}
+
+/// Returns the byte offsets of the starts of the lines of `text` that begin within `from..=to`.
+///
+/// `from` must be the start of a line and is always included.
+fn line_starts(text: &str, from: usize, to: usize) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut starts = vec![from];
+
+    for index in from..to {
+        match bytes[index] {
+            b'\n' => starts.push(index + 1),
+            b'\r' if bytes.get(index + 1) != Some(&b'\n') => starts.push(index + 1),
+            _ => {}
+        }
+    }
+
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VersionedTextDocumentIdentifier;
+
+    fn document(text: &str) -> TextDocument {
+        TextDocument::new(TextDocumentItem {
+            text: text.to_string(),
+            ..TextDocumentItem::default()
+        })
+    }
+
+    fn change(start: (u64, u64), end: (u64, u64), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent::new(
+            Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1)),
+            text.to_string(),
+        )
+    }
+
+    fn lines(document: &TextDocument) -> Vec<&str> {
+        (0..document.line_count()).map(|index| document.line(index).unwrap()).collect()
+    }
+
+    #[test]
+    fn full_replacement() {
+        let mut document = document("a\nb");
+
+        document.apply_change(&TextDocumentContentChangeEvent::full("c\r\nd\re\n".to_string())).unwrap();
+
+        assert_eq!(document.text(), "c\r\nd\re\n");
+        assert_eq!(lines(&document), ["c", "d", "e", ""]);
+    }
+
+    #[test]
+    fn multi_line_change() {
+        let mut document = document("ab\ncd\nef\ngh");
+
+        document.apply_change(&change((0, 1), (2, 1), "x\r\ny\rz")).unwrap();
+
+        assert_eq!(document.text(), "ax\r\ny\rzf\ngh");
+        assert_eq!(lines(&document), ["ax", "y", "zf", "gh"]);
+    }
+
+    #[test]
+    fn change_joins_line_terminator() {
+        let mut document = document("a\rx\nb");
+
+        document.apply_change(&change((1, 0), (1, 1), "")).unwrap();
+
+        assert_eq!(document.text(), "a\r\nb");
+        assert_eq!(lines(&document), ["a", "b"]);
+    }
+
+    #[test]
+    fn invalid_range() {
+        let mut document = document("a\nb");
+        let out_of_range = change((0, 0), (2, 0), "x");
+        let reversed = change((1, 0), (0, 0), "x");
+
+        assert_eq!(
+            document.apply_change(&out_of_range),
+            Err(DocumentError::InvalidRange(*out_of_range.range().unwrap()))
+        );
+        assert_eq!(document.apply_change(&reversed), Err(DocumentError::InvalidRange(*reversed.range().unwrap())));
+        assert_eq!(document.text(), "a\nb");
+    }
+
+    #[test]
+    fn failed_changes_are_not_applied() {
+        let mut document = document("a");
+        let params = |version, changes| {
+            DidChangeTextDocumentParams::new(
+                VersionedTextDocumentIdentifier {
+                    uri: DocumentUri::default(),
+                    version: Some(version),
+                },
+                changes,
+            )
+        };
+
+        assert_eq!(
+            document.apply_changes(&params(1, vec![change((0, 0), (0, 0), "b"), change((1, 0), (1, 0), "c")])),
+            Err(DocumentError::InvalidRange(Range::new(Position::new(1, 0), Position::new(1, 0))))
+        );
+        assert_eq!((document.text(), document.version()), ("a", 0));
+        assert_eq!(
+            document.apply_changes(&params(0, Vec::new())),
+            Err(DocumentError::StaleVersion { current: 0, received: 0 })
+        );
+        document.apply_changes(&params(1, vec![change((0, 1), (0, 1), "b")])).unwrap();
+        assert_eq!((document.text(), document.version()), ("ab", 1));
+    }
+
+    #[test]
+    fn position_at() {
+        let document = document("aé\r\n😀b\n");
+
+        assert_eq!(document.position_at(3), Position::new(0, 2));
+        assert_eq!(document.position_at(2), Position::new(0, 1));
+        assert_eq!(document.position_at(4), Position::new(0, 2));
+        assert_eq!(document.position_at(9), Position::new(1, 2));
+        assert_eq!(document.position_at(100), Position::new(2, 0));
+        assert_eq!(document.offset_at(Position::new(1, 2)), Some(9));
+        assert_eq!(document.offset_at(Position::new(3, 0)), None);
+    }
+
+    #[test]
+    fn line() {
+        let document = document("a\r\n\rb\n");
+
+        assert_eq!(document.line_count(), 4);
+        assert_eq!(lines(&document), ["a", "", "b", ""]);
+        assert_eq!(document.line(4), None);
+    }
+}
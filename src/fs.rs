@@ -0,0 +1,599 @@
+use crate::uri::DocumentUri;
+use crate::workspace::{apply_operations, apply_text_edits, operations, Applier, ApplyError, ApplyReport, Outcome};
+use crate::{
+    CreateFile, DeleteFile, DocumentChangeOperation, FailureHandlingKind, RenameFile, ResourceOperation,
+    ResourceOperationKind, TextDocumentEdit, WorkspaceEditCapabilities, WorkspaceEdit,
+};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Reverts an applied operation.
+#[derive(Debug)]
+pub(crate) enum Undo {
+    /// Restores the original text of an edited file.
+    Text(PathBuf, String),
+    /// Removes a created file and the folders created for it, then restores the staged file it
+    /// replaced.
+    Create(PathBuf, Vec<PathBuf>, Option<PathBuf>),
+    /// Moves a renamed file back, removes the folders created for it, then restores the staged
+    /// file it replaced.
+    Rename(PathBuf, PathBuf, Vec<PathBuf>, Option<PathBuf>),
+    /// Restores a staged deleted file or folder.
+    Delete(PathBuf, PathBuf),
+}
+
+/// A temporary folder that holds the originals of replaced or removed files and folders.
+///
+/// The folder and the originals in it are removed when the `Staging` is dropped.
+#[derive(Debug, Default)]
+struct Staging {
+    /// The folder, which is created when the first original is staged.
+    folder: Option<PathBuf>,
+    /// The number of staged originals.
+    count: usize,
+}
+
+impl Staging {
+    /// Moves `path` into the staging folder, returning its staged path.
+    fn stage(&mut self, path: &Path) -> io::Result<PathBuf> {
+        let folder = match &self.folder {
+            Some(folder) => folder.clone(),
+            None => {
+                let folder = create_staging_folder()?;
+
+                self.folder = Some(folder.clone());
+                folder
+            }
+        };
+        let staged = folder.join(self.count.to_string());
+
+        self.count += 1;
+        move_path(path, &staged)?;
+        Ok(staged)
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        // Originals that cannot be removed are left behind rather than failing an applied edit.
+        if let Some(folder) = &self.folder {
+            let _ = fs::remove_dir_all(folder);
+        }
+    }
+}
+
+/// Applies `WorkspaceEdit`s to files on disk.
+///
+/// Before a file or folder is replaced or removed, its original is staged in a temporary folder so
+/// that the operation can be rolled back. The original text of an edited file is kept in memory
+/// and the file is rewritten in place.
+#[derive(Clone, Debug)]
+pub struct FsApplier {
+    /// The kinds of resource operations that may be applied.
+    resource_operations: Vec<ResourceOperationKind>,
+    /// The strategy to handle a failure.
+    failure_handling: FailureHandlingKind,
+}
+
+impl FsApplier {
+    pub fn new(resource_operations: Vec<ResourceOperationKind>, failure_handling: FailureHandlingKind) -> Self {
+        FsApplier {
+            resource_operations,
+            failure_handling,
+        }
+    }
+
+    /// Creates a `FsApplier` that supports what the client advertises in `capabilities`.
+    ///
+    /// If the client does not advertise a failure handling strategy, `FailureHandlingKind::Abort`
+    /// is used.
+    pub fn from_capabilities(capabilities: &WorkspaceEditCapabilities) -> Self {
+        FsApplier::new(
+            capabilities.resource_operations().cloned().unwrap_or_default(),
+            capabilities.failure_handling().copied().unwrap_or(FailureHandlingKind::Abort),
+        )
+    }
+
+    /// Applies the operations of `edit` in order.
+    ///
+    /// Fails without modifying any file if `edit` has a resource operation that is not supported.
+    /// Versions of text document edits are not checked since files on disk are not versioned.
+    pub fn apply(&self, edit: &WorkspaceEdit) -> ApplyReport {
+        let operations = operations(edit);
+
+        if let Some((index, kind)) = operations.iter().enumerate().find_map(|(index, operation)| match operation {
+            DocumentChangeOperation::Op(op) if !self.resource_operations.contains(&op.kind()) => {
+                Some((index, op.kind()))
+            }
+            _ => None,
+        }) {
+            let mut outcomes = vec![Outcome::NotAttempted; operations.len()];
+
+            outcomes[index] = Outcome::Failed(ApplyError::Unsupported(kind));
+            return ApplyReport { outcomes };
+        }
+
+        apply_operations(&mut Staging::default(), &operations, self.failure_handling)
+    }
+}
+
+/// Operations on files stage the originals they replace or remove in the staging folder.
+impl Applier for Staging {
+    type Undo = Undo;
+
+    fn apply_operation(&mut self, operation: &DocumentChangeOperation) -> Result<Option<Undo>, ApplyError> {
+        match operation {
+            DocumentChangeOperation::Edit(edit) => edit_file(edit),
+            DocumentChangeOperation::Op(ResourceOperation::Create(create)) => create_file(create, self),
+            DocumentChangeOperation::Op(ResourceOperation::Rename(rename)) => rename_file(rename, self),
+            DocumentChangeOperation::Op(ResourceOperation::Delete(delete)) => delete_file(delete, self),
+        }
+    }
+
+    fn undo_operation(&mut self, undo: Undo) -> bool {
+        revert(&undo).is_ok()
+    }
+}
+
+/// Applies the text edits of `edit` to its file.
+///
+/// The file is rewritten in place, so that its permissions are kept and a symbolic link is
+/// followed.
+fn edit_file(edit: &TextDocumentEdit) -> Result<Option<Undo>, ApplyError> {
+    let path = to_path(&edit.text_document.uri)?;
+    let text = fs::read_to_string(&path).map_err(|error| io_error(&edit.text_document.uri, &error))?;
+    let new_text = apply_text_edits(&text, &edit.edits)?;
+
+    if let Err(error) = fs::write(&path, new_text) {
+        // The file may have been partially written.
+        let _ = fs::write(&path, &text);
+        return Err(io_error(&edit.text_document.uri, &error));
+    }
+
+    Ok(Some(Undo::Text(path, text)))
+}
+
+/// Creates the file of `create`, along with any missing parent folders.
+fn create_file(create: &CreateFile, staging: &mut Staging) -> Result<Option<Undo>, ApplyError> {
+    let options = create.options.as_option().copied().unwrap_or_default();
+    let path = to_path(&create.uri)?;
+    let mut replaced = None;
+
+    if path.exists() {
+        if !options.overwrite {
+            if options.ignore_if_exists {
+                return Ok(None);
+            }
+
+            return Err(ApplyError::AlreadyExists(create.uri.clone()));
+        }
+
+        replaced = Some(staging.stage(&path).map_err(|error| io_error(&create.uri, &error))?);
+    }
+
+    let result = create_parents(&path).and_then(|created| fs::write(&path, "").map(|_| created));
+
+    match result {
+        Ok(created) => Ok(Some(Undo::Create(path, created, replaced))),
+        Err(error) => {
+            if let Some(staged) = replaced {
+                let _ = move_path(&staged, &path);
+            }
+
+            Err(io_error(&create.uri, &error))
+        }
+    }
+}
+
+/// Renames the file or folder of `rename`, creating any missing parent folders of the target.
+fn rename_file(rename: &RenameFile, staging: &mut Staging) -> Result<Option<Undo>, ApplyError> {
+    let options = rename.options.as_option().copied().unwrap_or_default();
+    let old_path = to_path(&rename.old_uri)?;
+    let new_path = to_path(&rename.new_uri)?;
+    let mut replaced = None;
+
+    if !old_path.exists() {
+        return Err(ApplyError::NotFound(rename.old_uri.clone()));
+    }
+
+    if new_path.exists() {
+        if !options.overwrite {
+            if options.ignore_if_exists {
+                return Ok(None);
+            }
+
+            return Err(ApplyError::AlreadyExists(rename.new_uri.clone()));
+        }
+
+        replaced = Some(staging.stage(&new_path).map_err(|error| io_error(&rename.new_uri, &error))?);
+    }
+
+    let result = create_parents(&new_path).and_then(|created| fs::rename(&old_path, &new_path).map(|_| created));
+
+    match result {
+        Ok(created) => Ok(Some(Undo::Rename(old_path, new_path, created, replaced))),
+        Err(error) => {
+            if let Some(staged) = replaced {
+                let _ = move_path(&staged, &new_path);
+            }
+
+            Err(io_error(&rename.new_uri, &error))
+        }
+    }
+}
+
+/// Deletes the file or folder of `delete`.
+fn delete_file(delete: &DeleteFile, staging: &mut Staging) -> Result<Option<Undo>, ApplyError> {
+    let options = delete.options.as_option().copied().unwrap_or_default();
+    let path = to_path(&delete.uri)?;
+
+    if !path.exists() {
+        if options.ignore_if_not_exists {
+            return Ok(None);
+        }
+
+        return Err(ApplyError::NotFound(delete.uri.clone()));
+    }
+
+    if path.is_dir() && !options.recursive {
+        let mut entries = fs::read_dir(&path).map_err(|error| io_error(&delete.uri, &error))?;
+
+        if entries.next().is_some() {
+            return Err(ApplyError::FolderNotEmpty(delete.uri.clone()));
+        }
+    }
+
+    let staged = staging.stage(&path).map_err(|error| io_error(&delete.uri, &error))?;
+
+    Ok(Some(Undo::Delete(path, staged)))
+}
+
+/// Reverts an applied operation.
+fn revert(undo: &Undo) -> io::Result<()> {
+    match undo {
+        Undo::Text(path, text) => fs::write(path, text),
+        Undo::Delete(path, staged) => restore(staged, path),
+        Undo::Create(path, created, replaced) => {
+            fs::remove_file(path)?;
+            remove_folders(created)?;
+
+            match replaced {
+                Some(staged) => restore(staged, path),
+                None => Ok(()),
+            }
+        }
+        Undo::Rename(old_path, new_path, created, replaced) => {
+            fs::rename(new_path, old_path)?;
+            remove_folders(created)?;
+
+            match replaced {
+                Some(staged) => restore(staged, new_path),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Moves the staged original back to `path`, replacing the file at `path`.
+fn restore(staged: &Path, path: &Path) -> io::Result<()> {
+    if !staged.exists() {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+
+    if path.is_file() {
+        fs::remove_file(path)?;
+    }
+
+    move_path(staged, path)
+}
+
+/// Creates a new folder in the temporary directory for staging originals.
+fn create_staging_folder() -> io::Result<PathBuf> {
+    let mut index = 0;
+
+    loop {
+        let folder = env::temp_dir().join(format!("lsp_msg-{}-{}", process::id(), index));
+
+        match fs::create_dir(&folder) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => index += 1,
+            result => return result.map(|_| folder),
+        }
+    }
+}
+
+/// Moves the file or folder at `from` to `to`.
+///
+/// If `from` and `to` are on different file systems, `from` is copied and then removed.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(from, to)?;
+
+            if from.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Copies the file or folder at `from` to `to`.
+fn copy_path(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+
+    fs::create_dir(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+
+        copy_path(&entry.path(), &to.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
+/// Creates the missing parent folders of `path`, returning the created folders from outermost to
+/// innermost.
+fn create_parents(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut missing = path
+        .ancestors()
+        .skip(1)
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+
+    missing.reverse();
+
+    for folder in &missing {
+        fs::create_dir(folder)?;
+    }
+
+    Ok(missing)
+}
+
+/// Removes `folders` from innermost to outermost.
+fn remove_folders(folders: &[PathBuf]) -> io::Result<()> {
+    for folder in folders.iter().rev() {
+        fs::remove_dir(folder)?;
+    }
+
+    Ok(())
+}
+
+/// Converts an io error while accessing `uri` into an `ApplyError`.
+fn io_error(uri: &DocumentUri, error: &io::Error) -> ApplyError {
+    ApplyError::Io(format!("{}: {}", uri, error))
+}
+
+/// Converts a file URI into a path.
+fn to_path(uri: &DocumentUri) -> Result<PathBuf, ApplyError> {
+    uri.to_file_path().map_err(|_| ApplyError::InvalidUri(uri.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::tests::{edit, workspace_edit};
+    use jsonrpc_core::serde_json::{json, Value};
+
+    /// Returns an empty folder in the temporary directory for the test `name`.
+    fn folder(name: &str) -> PathBuf {
+        let folder = env::temp_dir().join(format!("lsp_msg-test-{}-{}", process::id(), name));
+
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir(&folder).unwrap();
+        folder
+    }
+
+    fn applier(failure_handling: FailureHandlingKind) -> FsApplier {
+        FsApplier::new(
+            vec![ResourceOperationKind::Create, ResourceOperationKind::Rename, ResourceOperationKind::Delete],
+            failure_handling,
+        )
+    }
+
+    fn uri(path: &Path) -> String {
+        DocumentUri::from_file_path(path).unwrap().to_string()
+    }
+
+    fn apply(applier: &FsApplier, operations: Vec<Value>) -> Vec<Outcome> {
+        applier.apply(&workspace_edit(operations)).outcomes
+    }
+
+    #[test]
+    fn create_options() {
+        let folder = folder("create");
+        let path = folder.join("a");
+        let applier = applier(FailureHandlingKind::Abort);
+
+        fs::write(&path, "a").unwrap();
+
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "create", "uri": uri(&path)})]),
+            [Outcome::Failed(ApplyError::AlreadyExists(DocumentUri::from_file_path(&path).unwrap()))]
+        );
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "create", "uri": uri(&path), "options": {"ignoreIfExists": true}})]),
+            [Outcome::Ignored]
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a");
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "create", "uri": uri(&path), "options": {"overwrite": true}})]),
+            [Outcome::Applied]
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "create", "uri": uri(&folder.join("b/c"))})]),
+            [Outcome::Applied]
+        );
+        assert!(folder.join("b/c").is_file());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn rename_options() {
+        let folder = folder("rename");
+        let (a, b) = (folder.join("a"), folder.join("b"));
+        let applier = applier(FailureHandlingKind::Abort);
+
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "rename", "oldUri": uri(&a), "newUri": uri(&b)})]),
+            [Outcome::Failed(ApplyError::AlreadyExists(DocumentUri::from_file_path(&b).unwrap()))]
+        );
+        assert_eq!(
+            apply(
+                &applier,
+                vec![json!({
+                    "kind": "rename", "oldUri": uri(&a), "newUri": uri(&b), "options": {"ignoreIfExists": true},
+                })]
+            ),
+            [Outcome::Ignored]
+        );
+        assert_eq!(
+            apply(
+                &applier,
+                vec![json!({"kind": "rename", "oldUri": uri(&a), "newUri": uri(&b), "options": {"overwrite": true}})]
+            ),
+            [Outcome::Applied]
+        );
+        assert!(!a.exists());
+        assert_eq!(fs::read_to_string(&b).unwrap(), "a");
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn delete_options() {
+        let folder = folder("delete");
+        let path = folder.join("a/b");
+        let applier = applier(FailureHandlingKind::Abort);
+
+        fs::create_dir(folder.join("a")).unwrap();
+        fs::write(&path, "b").unwrap();
+
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "delete", "uri": uri(&folder.join("c"))})]),
+            [Outcome::Failed(ApplyError::NotFound(DocumentUri::from_file_path(folder.join("c")).unwrap()))]
+        );
+        assert_eq!(
+            apply(
+                &applier,
+                vec![json!({"kind": "delete", "uri": uri(&folder.join("c")), "options": {"ignoreIfNotExists": true}})]
+            ),
+            [Outcome::Ignored]
+        );
+        assert_eq!(
+            apply(&applier, vec![json!({"kind": "delete", "uri": uri(&folder.join("a"))})]),
+            [Outcome::Failed(ApplyError::FolderNotEmpty(DocumentUri::from_file_path(folder.join("a")).unwrap()))]
+        );
+        assert_eq!(
+            apply(
+                &applier,
+                vec![json!({"kind": "delete", "uri": uri(&folder.join("a")), "options": {"recursive": true}})]
+            ),
+            [Outcome::Applied]
+        );
+        assert!(!folder.join("a").exists());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn unsupported_kind() {
+        let folder = folder("unsupported");
+        let path = folder.join("a");
+        let applier = FsApplier::new(vec![ResourceOperationKind::Create], FailureHandlingKind::Abort);
+
+        fs::write(&path, "a").unwrap();
+
+        assert_eq!(
+            apply(&applier, vec![edit(&uri(&path), "x"), json!({"kind": "delete", "uri": uri(&path)})]),
+            [Outcome::NotAttempted, Outcome::Failed(ApplyError::Unsupported(ResourceOperationKind::Delete))]
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a");
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn transactional_rolls_back() {
+        let folder = folder("transactional");
+        let (a, b, c) = (folder.join("a"), folder.join("b/c"), folder.join("d"));
+
+        fs::write(&a, "a").unwrap();
+        fs::write(&c, "d").unwrap();
+
+        assert_eq!(
+            apply(
+                &applier(FailureHandlingKind::Transactional),
+                vec![
+                    edit(&uri(&a), "x"),
+                    json!({"kind": "rename", "oldUri": uri(&a), "newUri": uri(&b)}),
+                    json!({"kind": "delete", "uri": uri(&c)}),
+                    json!({"kind": "delete", "uri": uri(&a)}),
+                ]
+            )[..3],
+            [Outcome::RolledBack, Outcome::RolledBack, Outcome::RolledBack]
+        );
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+        assert_eq!(fs::read_to_string(&c).unwrap(), "d");
+        assert!(!folder.join("b").exists());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn staged_originals_are_not_in_folder() {
+        let folder = folder("staging");
+        let path = folder.join("a/x.txt");
+
+        fs::create_dir(folder.join("a")).unwrap();
+        fs::write(&path, "x").unwrap();
+
+        assert_eq!(
+            apply(
+                &applier(FailureHandlingKind::Abort),
+                vec![
+                    edit(&uri(&path), "y"),
+                    json!({"kind": "delete", "uri": uri(&path)}),
+                    json!({"kind": "delete", "uri": uri(&folder.join("a"))}),
+                ]
+            ),
+            [Outcome::Applied, Outcome::Applied, Outcome::Applied]
+        );
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 0);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn edit_keeps_permissions_and_symbolic_links() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let folder = folder("permissions");
+        let (target, link) = (folder.join("a"), folder.join("b"));
+
+        fs::write(&target, "a").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        assert_eq!(apply(&applier(FailureHandlingKind::Abort), vec![edit(&uri(&link), "x")]), [Outcome::Applied]);
+        assert_eq!(
+            apply(
+                &applier(FailureHandlingKind::Transactional),
+                vec![edit(&uri(&link), "y"), edit(&uri(&folder.join("c")), "y")]
+            )[0],
+            Outcome::RolledBack
+        );
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "x");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o755);
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -1,9 +1,25 @@
+pub mod client;
+pub mod codec;
+pub mod diff;
+pub mod document;
+pub mod fs;
+pub mod server;
+pub mod sync;
+pub mod uri;
+pub mod workspace;
+
 use jsonrpc_core::Value;
 use lsp_msg_derive::{lsp_object, lsp_kind};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use uri::DocumentUri;
 
 #[lsp_kind]
+#[derive(Clone, Copy, Eq, PartialEq)]
 #[serde(untagged)]
 pub enum Elective<T> {
     Absent,
@@ -17,14 +33,177 @@ impl<T> Elective<T> {
             Elective::Present(_) => false,
         }
     }
+
+    /// Converts from `&Elective<T>` to `Option<&T>`.
+    pub fn as_option(&self) -> Option<&T> {
+        match self {
+            Elective::Absent => None,
+            Elective::Present(value) => Some(value),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Elective<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            None => Elective::Absent,
+            Some(value) => Elective::Present(value),
+        }
+    }
+}
+
+impl<T> From<Elective<T>> for Option<T> {
+    fn from(value: Elective<T>) -> Self {
+        match value {
+            Elective::Absent => None,
+            Elective::Present(value) => Some(value),
+        }
+    }
 }
 
+#[allow(clippy::derivable_impls)] // Deriving would require `T: Default`.
 impl<T> Default for Elective<T> {
     fn default() -> Self {
         Elective::Absent
     }
 }
 
+/// Implements a builder method and an accessor for each listed field of an `lsp_object`.
+///
+/// A `value` field is returned by copy, an `object` field by reference and an `elective` field as
+/// an `Option` of a reference.
+macro_rules! accessors {
+    ($name:ident { $($kind:ident $field:ident: $ty:ty => $setter:ident,)* }) => {
+        impl $name {
+            $(accessors!(@$kind $field: $ty => $setter);)*
+        }
+    };
+    (@value $field:ident: $ty:ty => $setter:ident) => {
+        #[doc = concat!("Sets `", stringify!($field), "`.")]
+        pub fn $setter(mut self, $field: $ty) -> Self {
+            self.$field = $field;
+            self
+        }
+
+        #[doc = concat!("Returns `", stringify!($field), "`.")]
+        pub fn $field(&self) -> $ty {
+            self.$field
+        }
+    };
+    (@object $field:ident: $ty:ty => $setter:ident) => {
+        #[doc = concat!("Sets `", stringify!($field), "`.")]
+        pub fn $setter(mut self, $field: $ty) -> Self {
+            self.$field = $field;
+            self
+        }
+
+        #[doc = concat!("Returns `", stringify!($field), "`.")]
+        pub fn $field(&self) -> &$ty {
+            &self.$field
+        }
+    };
+    (@elective $field:ident: $ty:ty => $setter:ident) => {
+        #[doc = concat!("Sets `", stringify!($field), "`.")]
+        pub fn $setter(mut self, $field: $ty) -> Self {
+            self.$field = Elective::Present($field);
+            self
+        }
+
+        #[doc = concat!("Returns `", stringify!($field), "` if present.")]
+        pub fn $field(&self) -> Option<&$ty> {
+            self.$field.as_option()
+        }
+    };
+}
+
+/// Defines a kind that is represented by a number and preserves numbers it does not know.
+macro_rules! number_kind {
+    ($(#[$attr:meta])* $name:ident { $($(#[$variant_attr:meta])* $variant:ident = $value:literal,)* }) => {
+        $(#[$attr])*
+        ///
+        /// Kinds are compared and hashed by `value`, so `Unknown` of a known value equals the
+        /// known kind.
+        #[derive(Clone, Copy, Debug)]
+        pub enum $name {
+            $($(#[$variant_attr])* $variant,)*
+            /// A kind that is not known by this version of the protocol.
+            Unknown(u64),
+        }
+
+        impl $name {
+            /// Returns the number that represents the kind.
+            pub fn value(self) -> u64 {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.value() == other.value()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl std::hash::Hash for $name {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.value().hash(state)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u64(self.value())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                u64::deserialize(deserializer).map($name::from)
+            }
+        }
+    };
+}
+
+/// A message that expects a response.
+pub trait Request {
+    /// The parameters of the request.
+    type Params: DeserializeOwned + Serialize;
+    /// The result of a successful response.
+    type Result: DeserializeOwned + Serialize;
+    /// The method of the request.
+    const METHOD: &'static str;
+}
+
+/// A message that does not expect a response.
+pub trait Notification {
+    /// The parameters of the notification.
+    type Params: DeserializeOwned + Serialize;
+    /// The method of the notification.
+    const METHOD: &'static str;
+}
+
+/// The `initialize` request.
+pub enum Initialize {}
+
+impl Request for Initialize {
+    type Params = InitializeParams;
+    type Result = InitializeResult;
+    const METHOD: &'static str = "initialize";
+}
+
 /// The first request from the client to the server.
 #[lsp_object]
 pub struct InitializeParams {
@@ -41,7 +220,7 @@ pub struct InitializeParams {
     /// The root URI of the workspace.
     ///
     /// If `Option::None`, no folder is open. Else, overrides `InitializeParams::root_path`.
-    pub root_uri: Option<String>,
+    pub root_uri: Option<DocumentUri>,
     /// User provided initialization options.
     pub initialization_options: Elective<Value>,
     /// Capabilities provided by the client.
@@ -67,13 +246,146 @@ pub struct ClientCapabilities {
     workspace: WorkspaceClientCapabilities,
     /// Text document specific client capabilities.
     text_document: TextDocumentClientCapabilities,
+    /// General client capabilities.
+    general: GeneralClientCapabilities,
     /// Experimental client capabilities.
     experimental: Elective<Value>,
 }
 
+accessors!(ClientCapabilities {
+    object workspace: WorkspaceClientCapabilities => with_workspace,
+    object text_document: TextDocumentClientCapabilities => with_text_document,
+    object general: GeneralClientCapabilities => with_general,
+    elective experimental: Value => with_experimental,
+});
+
+impl ClientCapabilities {
+    /// Returns the first position encoding preferred by the client that is in `supported`.
+    ///
+    /// `PositionEncodingKind::Other` encodings are skipped since their code units are unknown.
+    /// Returns `PositionEncodingKind::Utf16` if there is none, since all clients support it.
+    pub fn negotiate_position_encoding(&self, supported: &[PositionEncodingKind]) -> PositionEncodingKind {
+        self.general
+            .position_encodings()
+            .and_then(|encodings| {
+                encodings.iter().find(|encoding| {
+                    !matches!(encoding, PositionEncodingKind::Other(_)) && supported.contains(encoding)
+                })
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Defines capabilities the client provides regardless of the request.
+#[lsp_object(allow_missing)]
+pub struct GeneralClientCapabilities {
+    /// Position encodings supported by the client, in decreasing order of preference.
+    ///
+    /// If `Elective::Absent`, only `PositionEncodingKind::Utf16` is supported.
+    position_encodings: Elective<Vec<PositionEncodingKind>>,
+}
+
+accessors!(GeneralClientCapabilities {
+    elective position_encodings: Vec<PositionEncodingKind> => with_position_encodings,
+});
+
+/// Describes the code units counted by `Position::character`.
+///
+/// `PositionEncodingKind::Other` holds an encoding that is not known by the specification.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum PositionEncodingKind {
+    /// UTF-8 code units, which are bytes.
+    Utf8,
+    /// UTF-16 code units.
+    #[default]
+    Utf16,
+    /// UTF-32 code units, which are `char`s.
+    Utf32,
+    /// Any other encoding.
+    ///
+    /// Since UTF-16 must always be supported, its code units are counted as UTF-16 code units.
+    Other(String),
+}
+
+impl PositionEncodingKind {
+    /// Returns the identifier of the encoding.
+    pub fn as_str(&self) -> &str {
+        match self {
+            PositionEncodingKind::Utf8 => "utf-8",
+            PositionEncodingKind::Utf16 => "utf-16",
+            PositionEncodingKind::Utf32 => "utf-32",
+            PositionEncodingKind::Other(encoding) => encoding,
+        }
+    }
+
+    /// Returns the byte offset in `line` of the character offset `character`.
+    ///
+    /// If `character` is within a `char`, it is rounded up to the end of the `char`. If
+    /// `character` is greater than the length of `line`, it defaults to the length of `line`.
+    /// Code units of `PositionEncodingKind::Other` are counted as UTF-16 code units.
+    pub fn byte_offset(&self, line: &str, character: u64) -> usize {
+        let mut units = 0;
+
+        for (offset, c) in line.char_indices() {
+            if units >= character {
+                return offset;
+            }
+
+            units += self.len(c);
+        }
+
+        line.len()
+    }
+
+    /// Returns the character offset of the byte offset `offset` in `line`.
+    ///
+    /// If `offset` is within a `char`, it is rounded down to the start of the `char`. If `offset`
+    /// is greater than the length of `line`, it defaults to the length of `line`. Code units of
+    /// `PositionEncodingKind::Other` are counted as UTF-16 code units.
+    pub fn character_offset(&self, line: &str, offset: usize) -> u64 {
+        line.char_indices()
+            .take_while(|(index, c)| index + c.len_utf8() <= offset)
+            .map(|(_, c)| self.len(c))
+            .sum()
+    }
+
+    /// Returns the number of code units in `c`.
+    fn len(&self, c: char) -> u64 {
+        match self {
+            PositionEncodingKind::Utf8 => c.len_utf8() as u64,
+            PositionEncodingKind::Utf16 | PositionEncodingKind::Other(_) => c.len_utf16() as u64,
+            PositionEncodingKind::Utf32 => 1,
+        }
+    }
+}
+
+impl From<&str> for PositionEncodingKind {
+    fn from(value: &str) -> Self {
+        match value {
+            "utf-8" => PositionEncodingKind::Utf8,
+            "utf-16" => PositionEncodingKind::Utf16,
+            "utf-32" => PositionEncodingKind::Utf32,
+            _ => PositionEncodingKind::Other(value.to_string()),
+        }
+    }
+}
+
+impl Serialize for PositionEncodingKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PositionEncodingKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|encoding| PositionEncodingKind::from(encoding.as_str()))
+    }
+}
+
 /// Defines capabilities the client provides on the workspace.
 #[lsp_object(allow_missing)]
-struct WorkspaceClientCapabilities {
+pub struct WorkspaceClientCapabilities {
     /// Supports applying batch edits to the workspace by the request `workspace/applyEdit`.
     apply_edit: bool,
     /// Capabilities specific to `WorkspaceEdit`s.
@@ -92,9 +404,20 @@ struct WorkspaceClientCapabilities {
     configuration: bool,
 }
 
+accessors!(WorkspaceClientCapabilities {
+    value apply_edit: bool => with_apply_edit,
+    object workspace_edit: WorkspaceEditCapabilities => with_workspace_edit,
+    object did_change_configuration: DidChangeConfigurationCapabilities => with_did_change_configuration,
+    object did_change_watched_files: DidChangeWatchedFilesCapabilities => with_did_change_watched_files,
+    object symbol: SymbolCapabilities => with_symbol,
+    object execute_command: ExecuteCommandCapabilities => with_execute_command,
+    value workspace_folders: bool => with_workspace_folders,
+    value configuration: bool => with_configuration,
+});
+
 /// Defines capabilities the client provides on text documents.
 #[lsp_object(allow_missing)]
-struct TextDocumentClientCapabilities {
+pub struct TextDocumentClientCapabilities {
     /// Capabilities specific to synchronization.
     synchronization: SynchronizationCapabilities,
     /// Capabilities specific to the `textDocument/completion` request.
@@ -140,9 +463,33 @@ struct TextDocumentClientCapabilities {
     folding_range: FoldingRangeCapabilities,
 }
 
+accessors!(TextDocumentClientCapabilities {
+    object synchronization: SynchronizationCapabilities => with_synchronization,
+    object completion: CompletionCapabilities => with_completion,
+    object hover: HoverCapabilities => with_hover,
+    object signature_help: SignatureHelpCapabilities => with_signature_help,
+    object references: ReferencesCapabilities => with_references,
+    object document_highlight: DocumentHighlightCapabilities => with_document_highlight,
+    object document_symbol: DocumentSymbolCapabilities => with_document_symbol,
+    object formatting: FormattingCapabilities => with_formatting,
+    object range_formatting: RangeFormattingCapabilities => with_range_formatting,
+    object on_type_formatting: OnTypeFormattingCapabilities => with_on_type_formatting,
+    object declaration: DeclarationCapabilities => with_declaration,
+    object definition: DefinitionCapabilities => with_definition,
+    object type_definition: TypeDefinitionCapabilities => with_type_definition,
+    object implementation: ImplementationCapabilities => with_implementation,
+    object code_action: CodeActionCapabilities => with_code_action,
+    object code_lens: CodeLensCapabilities => with_code_lens,
+    object document_link: DocumentLinkCapabilities => with_document_link,
+    object color_provider: ColorProviderCapabilities => with_color_provider,
+    object rename: RenameCapabilities => with_rename,
+    object publish_diagnostics: PublishDiagnosticsCapabilities => with_publish_diagnostics,
+    object folding_range: FoldingRangeCapabilities => with_folding_range,
+});
+
 /// Defines capabilities specific to `WorkspaceEdit`s.
 #[lsp_object(allow_missing)]
-struct WorkspaceEditCapabilities {
+pub struct WorkspaceEditCapabilities {
     /// Supports versioned document changes in `WorkspaceEdit`s.
     document_changes: bool,
     /// The supported resource operations.
@@ -151,42 +498,72 @@ struct WorkspaceEditCapabilities {
     failure_handling: Elective<FailureHandlingKind>,
 }
 
+accessors!(WorkspaceEditCapabilities {
+    value document_changes: bool => with_document_changes,
+    elective resource_operations: Vec<ResourceOperationKind> => with_resource_operations,
+    elective failure_handling: FailureHandlingKind => with_failure_handling,
+});
+
 /// Defines capabilities specific to the `workspace/didChangeConfiguration` notification.
 #[lsp_object(allow_missing, dynamic_registration = "`workspace/didChangeConfiguration` notification")]
-struct DidChangeConfigurationCapabilities {
+pub struct DidChangeConfigurationCapabilities {
 }
 
+accessors!(DidChangeConfigurationCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `workspace/didChangeWatchedFiles` notification.
 #[lsp_object(allow_missing, dynamic_registration = "`workspace/didChangeWatchedFiles` notification")]
-struct DidChangeWatchedFilesCapabilities {
+pub struct DidChangeWatchedFilesCapabilities {
 }
 
+accessors!(DidChangeWatchedFilesCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `workspace/symbol` request.
 #[lsp_object(allow_missing, dynamic_registration = "`workspace/symbol` request")]
-struct SymbolCapabilities {
+pub struct SymbolCapabilities {
     /// Capabilities specific to the `SymbolKind` in the `workspace/symbol` request.
     symbol_kind: SymbolKindCapabilities,
 }
 
+accessors!(SymbolCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    object symbol_kind: SymbolKindCapabilities => with_symbol_kind,
+});
+
 /// Defines capabilities specific to the `workspace/executeCommand` request.
 #[lsp_object(allow_missing, dynamic_registration = "`workspace/executeCommand` request")]
-struct ExecuteCommandCapabilities {
+pub struct ExecuteCommandCapabilities {
 }
 
+accessors!(ExecuteCommandCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to text document synchronization.
 #[lsp_object(allow_missing, dynamic_registration = "text document synchronization")]
-struct SynchronizationCapabilities {
+pub struct SynchronizationCapabilities {
     /// Supports the `textDocument/willSave` notification.
     will_save: bool,
-    /// Supports the `textDocument/willSaveWaitUntil` notification.
-    will_save_until: bool,
+    /// Supports the `textDocument/willSaveWaitUntil` request.
+    will_save_wait_until: bool,
     /// Supports the `textDocument/didSave` notification.
     did_save: bool,
 }
 
+accessors!(SynchronizationCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value will_save: bool => with_will_save,
+    value will_save_wait_until: bool => with_will_save_wait_until,
+    value did_save: bool => with_did_save,
+});
+
 /// Defines capabilities specific to the `textDocument/completion` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/completion` request")]
-struct CompletionCapabilities {
+pub struct CompletionCapabilities {
     /// Capabilities specific to `CompletionItem`s.
     completion_item: CompletionItemCapabilities,
     /// Capabilities specific to `CompletionItemKinds`s.
@@ -195,112 +572,265 @@ struct CompletionCapabilities {
     context_support: bool,
 }
 
+accessors!(CompletionCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    object completion_item: CompletionItemCapabilities => with_completion_item,
+    object completion_item_kind: CompletionItemKindCapabilities => with_completion_item_kind,
+    value context_support: bool => with_context_support,
+});
+
 /// Defines capabilities specific to the `textDocument/hover` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/hover` request", markup_kind_list = "content")]
-struct HoverCapabilities {
+pub struct HoverCapabilities {
 }
 
+accessors!(HoverCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    object content_format: Vec<MarkupKind> => with_content_format,
+});
+
 /// Defines capabilities specific to the `textDocument/signatureHelp` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/signatureHelp` request")]
-struct SignatureHelpCapabilities {
+pub struct SignatureHelpCapabilities {
     /// Capabilities specific to `SignatureInformation`s.
     signature_information: SignatureInformationCapabilities,
 }
 
+accessors!(SignatureHelpCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    object signature_information: SignatureInformationCapabilities => with_signature_information,
+});
+
 /// Defines capabilities specific to the `textDocument/references` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/references` request")]
-struct ReferencesCapabilities {
+pub struct ReferencesCapabilities {
 }
 
+accessors!(ReferencesCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/documentHighlight` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/documentHighlight` request")]
-struct DocumentHighlightCapabilities {
+pub struct DocumentHighlightCapabilities {
 }
 
+accessors!(DocumentHighlightCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/documentSymbol` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/documentSymbol` request")]
-struct DocumentSymbolCapabilities {
+pub struct DocumentSymbolCapabilities {
     /// Capabilities specific to `SymbolKind` in the `textDocument/documentSymbol` request.
     symbol_kind: SymbolKindCapabilities,
     /// Supports hierarchical document symbols.
     hierarchical_document_symbol_support: bool,
 }
 
+accessors!(DocumentSymbolCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    object symbol_kind: SymbolKindCapabilities => with_symbol_kind,
+    value hierarchical_document_symbol_support: bool => with_hierarchical_document_symbol_support,
+});
+
 /// Defines capabilities specific to the `textDocument/formatting` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/formatting` request")]
-struct FormattingCapabilities {
+pub struct FormattingCapabilities {
 }
 
+accessors!(FormattingCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/rangeFormatting` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/rangeFormatting` request")]
-struct RangeFormattingCapabilities {
+pub struct RangeFormattingCapabilities {
 }
 
+accessors!(RangeFormattingCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/onTypeFormatting` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/onTypeFormatting` request")]
-struct OnTypeFormattingCapabilities {
+pub struct OnTypeFormattingCapabilities {
 }
 
+accessors!(OnTypeFormattingCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/declaration` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/declaration` request", link_support = "declaration")]
-struct DeclarationCapabilities {
+pub struct DeclarationCapabilities {
 }
 
+accessors!(DeclarationCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value link_support: bool => with_link_support,
+});
+
 /// Defines capabilities specific to the `textDocument/definition` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/definition` request", link_support = "definition")]
-struct DefinitionCapabilities {
+pub struct DefinitionCapabilities {
 }
 
+accessors!(DefinitionCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value link_support: bool => with_link_support,
+});
+
 /// Defines capabilities specific to the `textDocument/typeDefinition` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/typeDefinition` request", link_support = "definition")]
-struct TypeDefinitionCapabilities {
+pub struct TypeDefinitionCapabilities {
 }
 
+accessors!(TypeDefinitionCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value link_support: bool => with_link_support,
+});
+
 /// Defines capabilities specific to the `textDocument/implementation` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/implementation` request", link_support = "implementation")]
-struct ImplementationCapabilities {
+pub struct ImplementationCapabilities {
 }
 
+accessors!(ImplementationCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value link_support: bool => with_link_support,
+});
+
 /// Defines capabilities specific to the `textDocument/codeAction` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/codeAction` request")]
-struct CodeActionCapabilities {
+pub struct CodeActionCapabilities {
     /// Capabilities specific to code action literals.
+    ///
+    /// If absent, the server may only return `Command`s.
     code_action_literal_support: Elective<CodeActionLiteralCapabilities>,
+    /// Supports the `is_preferred` property of a `CodeAction`.
+    is_preferred_support: bool,
+    /// Supports the `disabled` property of a `CodeAction`.
+    disabled_support: bool,
+    /// Preserves the `data` property of a `CodeAction` between the `textDocument/codeAction` and
+    /// `codeAction/resolve` requests.
+    data_support: bool,
+    /// Capabilities specific to the `codeAction/resolve` request.
+    resolve_support: Elective<CodeActionResolveCapabilities>,
+}
+
+accessors!(CodeActionCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    elective code_action_literal_support: CodeActionLiteralCapabilities => with_code_action_literal_support,
+    value is_preferred_support: bool => with_is_preferred_support,
+    value disabled_support: bool => with_disabled_support,
+    value data_support: bool => with_data_support,
+    elective resolve_support: CodeActionResolveCapabilities => with_resolve_support,
+});
+
+impl CodeActionCapabilities {
+    /// Returns the items of `response` that the client supports.
+    ///
+    /// If code action literals are not supported, each `CodeAction` is replaced by its `Command`
+    /// and is removed if it has an edit or no command. Otherwise, a disabled `CodeAction` is
+    /// removed if disabled code actions are not supported and `is_preferred` is removed if it is
+    /// not supported.
+    pub fn supported_actions(&self, response: Vec<CommandOrCodeAction>) -> Vec<CommandOrCodeAction> {
+        let supports_literals = !self.code_action_literal_support.is_absent();
+
+        response
+            .into_iter()
+            .filter_map(|item| match item {
+                CommandOrCodeAction::Command(command) => Some(CommandOrCodeAction::Command(command)),
+                CommandOrCodeAction::CodeAction(action) if !supports_literals => match *action {
+                    CodeAction {
+                        edit: Elective::Absent,
+                        command: Elective::Present(command),
+                        ..
+                    } => Some(CommandOrCodeAction::Command(command)),
+                    _ => None,
+                },
+                CommandOrCodeAction::CodeAction(mut action) => {
+                    if !self.disabled_support && !action.disabled.is_absent() {
+                        return None;
+                    }
+
+                    if !self.is_preferred_support {
+                        action.is_preferred = Elective::Absent;
+                    }
+
+                    Some(CommandOrCodeAction::CodeAction(action))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Describes capabilities specific to the `codeAction/resolve` request.
+#[lsp_object(allow_missing)]
+pub struct CodeActionResolveCapabilities {
+    /// The properties of a `CodeAction` that can be resolved lazily.
+    properties: Vec<String>,
 }
 
+accessors!(CodeActionResolveCapabilities {
+    object properties: Vec<String> => with_properties,
+});
+
 /// Defines capabilities specific to the `textDocument/codeLens` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/codeLens` request")]
-struct CodeLensCapabilities {
+pub struct CodeLensCapabilities {
 }
 
+accessors!(CodeLensCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/documentLink` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/documentLink` request")]
-struct DocumentLinkCapabilities {
+pub struct DocumentLinkCapabilities {
 }
 
+accessors!(DocumentLinkCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/documentColor` and
 /// `textDocument/colorPresentation` requests.
 #[lsp_object(allow_missing, dynamic_registration = "color provider")]
-struct ColorProviderCapabilities {
+pub struct ColorProviderCapabilities {
 }
 
+accessors!(ColorProviderCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+});
+
 /// Defines capabilities specific to the `textDocument/rename` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/rename` request")]
-struct RenameCapabilities {
+pub struct RenameCapabilities {
     /// Supports testing for validity of rename operations before execution.
     prepare_support: bool,
 }
 
+accessors!(RenameCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value prepare_support: bool => with_prepare_support,
+});
+
 /// Defines capabilities specific to the `textDocument/publishDiagnostics` notification.
 #[lsp_object(allow_missing)]
-struct PublishDiagnosticsCapabilities {
+pub struct PublishDiagnosticsCapabilities {
     /// Supports diagnostics with related information.
     related_information: bool,
 }
 
+accessors!(PublishDiagnosticsCapabilities {
+    value related_information: bool => with_related_information,
+});
+
 /// Defines capabilities specific to the `textDocument/foldingRange` request.
 #[lsp_object(allow_missing, dynamic_registration = "`textDocument/foldingRange` request")]
-struct FoldingRangeCapabilities {
+pub struct FoldingRangeCapabilities {
     /// The preferred maximum number of folding ranges per document.
     ///
     /// Serves as a hint; servers are free to follow the limit.
@@ -309,9 +839,16 @@ struct FoldingRangeCapabilities {
     line_folding_only: bool,
 }
 
+accessors!(FoldingRangeCapabilities {
+    value dynamic_registration: bool => with_dynamic_registration,
+    value range_limit: u64 => with_range_limit,
+    value line_folding_only: bool => with_line_folding_only,
+});
+
 /// The kind of resource operations.
 #[lsp_kind]
-enum ResourceOperationKind {
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum ResourceOperationKind {
     /// Creating new files and folders.
     Create,
     /// Renaming existing files and folders.
@@ -322,7 +859,8 @@ enum ResourceOperationKind {
 
 /// The strategy of the client to handle a failure to apply a `WorkspaceEdit`.
 #[lsp_kind]
-enum FailureHandlingKind {
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum FailureHandlingKind {
     /// Operations are simply aborted if one of the changes fails.
     ///
     /// All operations executed before the failing operation stayed executed.
@@ -341,19 +879,43 @@ enum FailureHandlingKind {
 
 /// Describes capabilities specific to `SymbolKind`s.
 #[lsp_object(allow_missing)]
-struct SymbolKindCapabilities {
+pub struct SymbolKindCapabilities {
     // TODO: Does it make sense to add an attribute for adding value_set property?
-    // TODO: Is there a way for serde to handle unknown SymbolKinds?
     /// The supported `SymbolKind` values.
     ///
     /// If absent, only supports the `SymbolKind`s <= `SymbolKind::Array`. Otherwise, falls back to
     /// a default value when unknown.
-    value_set: Elective<Vec<u64>>,
+    value_set: Elective<Vec<SymbolKind>>,
+}
+
+accessors!(SymbolKindCapabilities {
+    elective value_set: Vec<SymbolKind> => with_value_set,
+});
+
+impl SymbolKindCapabilities {
+    /// Returns if `kind` is supported.
+    pub fn supports(&self, kind: SymbolKind) -> bool {
+        match self.value_set.as_option() {
+            Some(value_set) => value_set.contains(&kind),
+            None => (SymbolKind::File.value()..=SymbolKind::Array.value()).contains(&kind.value()),
+        }
+    }
+
+    /// Returns `kind` if it is supported, otherwise the closest supported `SymbolKind`.
+    ///
+    /// Defaults to `SymbolKind::Variable`, which a client falls back from if it is not supported.
+    pub fn closest(&self, kind: SymbolKind) -> SymbolKind {
+        Some(kind)
+            .into_iter()
+            .chain(kind.fallback())
+            .find(|&kind| self.supports(kind))
+            .unwrap_or(SymbolKind::Variable)
+    }
 }
 
 /// Describes capabilities specific to `CompletionItem`s.
 #[lsp_object(allow_missing, markup_kind_list = "documentation")]
-struct CompletionItemCapabilities {
+pub struct CompletionItemCapabilities {
     /// Supports snippets as insert text.
     snippet_support: bool,
     /// Supports commit characters on a `CompletionItem`.
@@ -364,21 +926,58 @@ struct CompletionItemCapabilities {
     preselect_support: bool,
 }
 
+accessors!(CompletionItemCapabilities {
+    object documentation_format: Vec<MarkupKind> => with_documentation_format,
+    value snippet_support: bool => with_snippet_support,
+    value commit_characters_support: bool => with_commit_characters_support,
+    value deprecated_support: bool => with_deprecated_support,
+    value preselect_support: bool => with_preselect_support,
+});
+
 /// Describes capabilities specific to `CompletionItemKind`s.
 #[lsp_object(allow_missing)]
-struct CompletionItemKindCapabilities {
-    // TODO: Is there a way for serde to handle unknown CodeActionKinds?
+pub struct CompletionItemKindCapabilities {
     /// The supported `CompletionItemKind`s.
     ///
     /// If absent, only supports the `CompletionItemKind`s <= `CompletionItemKind::Reference`.
     /// Otherwise, falls back to a default value when unknown.
-    value_set: Elective<Vec<u64>>,
+    value_set: Elective<Vec<CompletionItemKind>>,
+}
+
+accessors!(CompletionItemKindCapabilities {
+    elective value_set: Vec<CompletionItemKind> => with_value_set,
+});
+
+impl CompletionItemKindCapabilities {
+    /// Returns if `kind` is supported.
+    pub fn supports(&self, kind: CompletionItemKind) -> bool {
+        match self.value_set.as_option() {
+            Some(value_set) => value_set.contains(&kind),
+            None => {
+                (CompletionItemKind::Text.value()..=CompletionItemKind::Reference.value()).contains(&kind.value())
+            }
+        }
+    }
+
+    /// Returns `kind` if it is supported, otherwise the closest supported `CompletionItemKind`.
+    ///
+    /// Defaults to `CompletionItemKind::Text`, which a client falls back from if it is not
+    /// supported.
+    pub fn closest(&self, kind: CompletionItemKind) -> CompletionItemKind {
+        Some(kind)
+            .into_iter()
+            .chain(kind.fallback())
+            .find(|&kind| self.supports(kind))
+            .unwrap_or(CompletionItemKind::Text)
+    }
 }
 
 /// Describes the types of content in various result literals.
 #[lsp_kind]
-enum MarkupKind {
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum MarkupKind {
     /// Plain text.
+    #[default]
     Plaintext,
     /// Markdown.
     Markdown,
@@ -386,117 +985,244 @@ enum MarkupKind {
 
 /// Describes capabilities specific to `SignatureInformation`s.
 #[lsp_object(allow_missing, markup_kind_list = "documentation")]
-struct SignatureInformationCapabilities {
+pub struct SignatureInformationCapabilities {
     /// Capabilities specific to parameter information.
     parameter_information: ParameterInformationCapabilities,
 }
 
+accessors!(SignatureInformationCapabilities {
+    object documentation_format: Vec<MarkupKind> => with_documentation_format,
+    object parameter_information: ParameterInformationCapabilities => with_parameter_information,
+});
+
 /// Describes capabilities specific to code action literals
 #[lsp_object(allow_missing)]
-struct CodeActionLiteralCapabilities {
+pub struct CodeActionLiteralCapabilities {
     /// Capabilities specific to `CodeActionKind`s.
     code_action_kind: CodeActionKindCapabilities,
 }
 
-/// A symbol kind.
-#[lsp_kind]
-enum SymbolKind {
-    File = 1,
-    Module,
-    Namespace,
-    Package,
-    Class,
-    Method,
-    Property,
-    Field,
-    Constructor,
-    Enum,
-    Interface,
-    Function,
-    Variable,
-    Constant,
-    String,
-    Number,
-    Boolean,
-    Array,
-    Object,
-    Key,
-    Null,
-    EnumMember,
-    Struct,
-    Event,
-    Operator,
-    TypeParameter,
-}
-
-/// The kind of a `CompletionItem`.
-#[lsp_kind]
-enum CompletionItemKind {
-    Text = 1,
-    Method,
-    Function,
-    Constructor,
-    Field,
-    Variable,
-    Class,
-    Interface,
-    Module,
-    Property,
-    Unit,
-    Value,
-    Enum,
-    Keyword,
-    Snippet,
-    Color,
-    File,
-    Reference,
-    Folder,
-    EnumMember,
-    Constant,
-    Struct,
-    Event,
-    Operator,
-    TypeParameter,
+accessors!(CodeActionLiteralCapabilities {
+    object code_action_kind: CodeActionKindCapabilities => with_code_action_kind,
+});
+
+
+number_kind! {
+    /// A symbol kind.
+    SymbolKind {
+        File = 1,
+        Module = 2,
+        Namespace = 3,
+        Package = 4,
+        Class = 5,
+        Method = 6,
+        Property = 7,
+        Field = 8,
+        Constructor = 9,
+        Enum = 10,
+        Interface = 11,
+        Function = 12,
+        Variable = 13,
+        Constant = 14,
+        String = 15,
+        Number = 16,
+        Boolean = 17,
+        Array = 18,
+        Object = 19,
+        Key = 20,
+        Null = 21,
+        EnumMember = 22,
+        Struct = 23,
+        Event = 24,
+        Operator = 25,
+        TypeParameter = 26,
+    }
+}
+
+impl SymbolKind {
+    /// Returns the `SymbolKind` <= `SymbolKind::Array` that is most similar to a kind added by
+    /// later versions of the protocol.
+    fn fallback(self) -> Option<Self> {
+        match self {
+            SymbolKind::Object | SymbolKind::TypeParameter => Some(SymbolKind::Variable),
+            SymbolKind::Key => Some(SymbolKind::Property),
+            SymbolKind::Null | SymbolKind::EnumMember => Some(SymbolKind::Constant),
+            SymbolKind::Struct => Some(SymbolKind::Class),
+            SymbolKind::Event => Some(SymbolKind::Field),
+            SymbolKind::Operator => Some(SymbolKind::Function),
+            _ => None,
+        }
+    }
+}
+
+number_kind! {
+    /// The kind of a `CompletionItem`.
+    CompletionItemKind {
+        Text = 1,
+        Method = 2,
+        Function = 3,
+        Constructor = 4,
+        Field = 5,
+        Variable = 6,
+        Class = 7,
+        Interface = 8,
+        Module = 9,
+        Property = 10,
+        Unit = 11,
+        Value = 12,
+        Enum = 13,
+        Keyword = 14,
+        Snippet = 15,
+        Color = 16,
+        File = 17,
+        Reference = 18,
+        Folder = 19,
+        EnumMember = 20,
+        Constant = 21,
+        Struct = 22,
+        Event = 23,
+        Operator = 24,
+        TypeParameter = 25,
+    }
+}
+
+impl CompletionItemKind {
+    /// Returns the `CompletionItemKind` <= `CompletionItemKind::Reference` that is most similar to
+    /// a kind added by later versions of the protocol.
+    fn fallback(self) -> Option<Self> {
+        match self {
+            CompletionItemKind::Folder => Some(CompletionItemKind::File),
+            CompletionItemKind::EnumMember | CompletionItemKind::Constant => Some(CompletionItemKind::Value),
+            CompletionItemKind::Struct | CompletionItemKind::TypeParameter => Some(CompletionItemKind::Class),
+            CompletionItemKind::Event => Some(CompletionItemKind::Property),
+            CompletionItemKind::Operator => Some(CompletionItemKind::Keyword),
+            _ => None,
+        }
+    }
 }
 
 /// Describes capabilities specific to parameter information.
 #[lsp_object(allow_missing)]
-struct ParameterInformationCapabilities {
+pub struct ParameterInformationCapabilities {
     /// Supports processing label offsets instread of a simple label string.
     label_offset_support: bool,
 }
 
+accessors!(ParameterInformationCapabilities {
+    value label_offset_support: bool => with_label_offset_support,
+});
+
 /// Describes capabilities specific to `CodeActionKind`s.
 #[lsp_object(allow_missing)]
-struct CodeActionKindCapabilities {
-    // TODO: Does it make sense to add an attribute for the fall back statement?
-    // TODO: Is there a way for serde to handle unknown CodeActionKinds?
+pub struct CodeActionKindCapabilities {
     /// The supported `CodeActionKind` values.
     ///
     /// If present, falls back to a default value when unknown.
-    value_set: Elective<Vec<String>>,
+    value_set: Elective<Vec<CodeActionKind>>,
+}
+
+accessors!(CodeActionKindCapabilities {
+    elective value_set: Vec<CodeActionKind> => with_value_set,
+});
+
+impl CodeActionKindCapabilities {
+    /// Returns if `kind` is a subkind of a supported `CodeActionKind`.
+    pub fn supports(&self, kind: &CodeActionKind) -> bool {
+        self.value_set
+            .as_option()
+            .is_some_and(|value_set| value_set.iter().any(|supported| supported.contains(kind)))
+    }
+}
+
+/// The kind of a code action.
+///
+/// Kinds are a hierarchy of identifiers separated by `.`, such as `refactor.extract.function`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct CodeActionKind(Cow<'static, str>);
+
+impl CodeActionKind {
+    /// The empty kind, which contains every kind.
+    pub const EMPTY: CodeActionKind = CodeActionKind(Cow::Borrowed(""));
+    /// Fixes a problem.
+    pub const QUICK_FIX: CodeActionKind = CodeActionKind(Cow::Borrowed("quickfix"));
+    /// Refactors code.
+    pub const REFACTOR: CodeActionKind = CodeActionKind(Cow::Borrowed("refactor"));
+    /// Extracts code into a method, function, variable, etc.
+    pub const REFACTOR_EXTRACT: CodeActionKind = CodeActionKind(Cow::Borrowed("refactor.extract"));
+    /// Inlines a function, variable, constant, etc.
+    pub const REFACTOR_INLINE: CodeActionKind = CodeActionKind(Cow::Borrowed("refactor.inline"));
+    /// Rewrites code, such as converting a function to an arrow function.
+    pub const REFACTOR_REWRITE: CodeActionKind = CodeActionKind(Cow::Borrowed("refactor.rewrite"));
+    /// Applies to the entire file.
+    pub const SOURCE: CodeActionKind = CodeActionKind(Cow::Borrowed("source"));
+    /// Organizes imports.
+    pub const SOURCE_ORGANIZE_IMPORTS: CodeActionKind = CodeActionKind(Cow::Borrowed("source.organizeImports"));
+    /// Fixes all auto-fixable problems.
+    pub const SOURCE_FIX_ALL: CodeActionKind = CodeActionKind(Cow::Borrowed("source.fixAll"));
+
+    /// Creates a `CodeActionKind` with the identifier `kind`.
+    pub fn new<S: Into<String>>(kind: S) -> Self {
+        CodeActionKind(Cow::Owned(kind.into()))
+    }
+
+    /// Returns the identifier of the kind.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns if `kind` is the same as or a subkind of the kind.
+    pub fn contains(&self, kind: &CodeActionKind) -> bool {
+        self.0.is_empty()
+            || (kind.0.starts_with(self.as_str())
+                && matches!(kind.0.as_bytes().get(self.0.len()), None | Some(b'.')))
+    }
+
+    /// Returns if the kind is the same as or a subkind of `kind`.
+    pub fn is_subkind_of(&self, kind: &CodeActionKind) -> bool {
+        kind.contains(self)
+    }
+
+    /// Returns if the kind is a subkind of any kind in `filter`.
+    ///
+    /// An empty `filter` matches every kind.
+    pub fn matches(&self, filter: &[CodeActionKind]) -> bool {
+        filter.is_empty() || filter.iter().any(|kind| kind.contains(self))
+    }
+}
+
+impl Display for CodeActionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for CodeActionKind {
+    fn from(value: &str) -> Self {
+        CodeActionKind::new(value)
+    }
 }
 
-// TODO: Is there a way to represent hierarchy of CodeActionKinds using serde?
+impl From<String> for CodeActionKind {
+    fn from(value: String) -> Self {
+        CodeActionKind::new(value)
+    }
+}
 
 #[lsp_kind]
+#[derive(Default)]
 pub enum TraceKind {
+    #[default]
     Off,
     Messages,
     Verbose,
 }
 
-impl Default for TraceKind {
-    fn default() -> Self {
-        TraceKind::Off
-    }
-}
-
 /// Describes a folder in a workspace.
 #[lsp_object]
 pub struct WorkspaceFolder {
     /// The associated URI.
-    uri: String,
+    uri: DocumentUri,
     /// The name as used in the user interface.
     name: String,
 }
@@ -516,9 +1242,9 @@ pub struct ServerCapabilities {
     /// Provides hover support.
     hover_provider: bool,
     /// Provides completion support.
-    completion_provider: CompletionOptions,
+    completion_provider: Elective<CompletionOptions>,
     /// Provides signature help support.
-    signature_help_provider: SignatureHelpOptions,
+    signature_help_provider: Elective<SignatureHelpOptions>,
     /// Provides goto definition support.
     definition_provider: bool,
     /// Provides goto type definition support.
@@ -536,17 +1262,17 @@ pub struct ServerCapabilities {
     /// Provides code actions.
     code_action_provider: BooleanOrOptions<CodeActionOptions>,
     /// Provides code lens.
-    code_lens_provider: CodeLensOptions,
+    code_lens_provider: Elective<CodeLensOptions>,
     /// Provides document formatting.
     document_formatting_provider: bool,
     /// Provides document range formatting.
     document_range_formatting_provider: bool,
     /// Provides document formatting on typing.
-    document_on_type_formatting_provider: DocumentOnTypeFormattingOptions,
+    document_on_type_formatting_provider: Elective<DocumentOnTypeFormattingOptions>,
     /// Provides rename support.
     rename_provider: BooleanOrOptions<RenameOptions>,
     /// Provides document link support.
-    document_link_provider: DocumentLinkOptions,
+    document_link_provider: Elective<DocumentLinkOptions>,
     /// Provies color provider support.
     color_provider: BooleanOrOptionsOrStaticDocumentSelectorOptions<ColorProviderOptions>,
     /// Provides folding provider support.
@@ -554,128 +1280,485 @@ pub struct ServerCapabilities {
     /// Provides goto declaration support.
     declaration_provider: BooleanOrOptions<GotoOptions>,
     /// Provides execute command support.
-    execute_command_provider: ExecuteCommandOptions,
+    execute_command_provider: Elective<ExecuteCommandOptions>,
     /// Server capabilities specific to a workspace.
     workspace: WorkspaceOptions,
+    /// Position encoding chosen by the server from those supported by the client.
+    ///
+    /// If `Elective::Absent`, defaults to `PositionEncodingKind::Utf16`.
+    position_encoding: Elective<PositionEncodingKind>,
     /// Experimental server capabilities.
     experimental: Elective<Value>,
 }
 
-#[lsp_kind]
-#[serde(untagged)]
-pub enum TextDocumentSyncProvider {
-    Options(TextDocumentSyncOptions),
-    Kind(TextDocumentSyncKind),
-}
+accessors!(ServerCapabilities {
+    object text_document_sync: TextDocumentSyncProvider => with_text_document_sync,
+    value hover_provider: bool => with_hover_provider,
+    elective completion_provider: CompletionOptions => with_completion_provider,
+    elective signature_help_provider: SignatureHelpOptions => with_signature_help_provider,
+    value definition_provider: bool => with_definition_provider,
+    object type_definition_provider: BooleanOrOptions<GotoOptions> => with_type_definition_provider,
+    object implementation_provider: BooleanOrOptions<GotoOptions> => with_implementation_provider,
+    value references_provider: bool => with_references_provider,
+    value document_highlight_provider: bool => with_document_highlight_provider,
+    value document_symbol_provider: bool => with_document_symbol_provider,
+    value workspace_symbol_provider: bool => with_workspace_symbol_provider,
+    object code_action_provider: BooleanOrOptions<CodeActionOptions> => with_code_action_provider,
+    elective code_lens_provider: CodeLensOptions => with_code_lens_provider,
+    value document_formatting_provider: bool => with_document_formatting_provider,
+    value document_range_formatting_provider: bool => with_document_range_formatting_provider,
+    elective document_on_type_formatting_provider: DocumentOnTypeFormattingOptions => with_document_on_type_formatting_provider,
+    object rename_provider: BooleanOrOptions<RenameOptions> => with_rename_provider,
+    elective document_link_provider: DocumentLinkOptions => with_document_link_provider,
+    object color_provider: BooleanOrOptionsOrStaticDocumentSelectorOptions<ColorProviderOptions> => with_color_provider,
+    object folding_range_provider: BooleanOrOptionsOrStaticDocumentSelectorOptions<FoldingRangeProviderOptions> => with_folding_range_provider,
+    object declaration_provider: BooleanOrOptions<GotoOptions> => with_declaration_provider,
+    elective execute_command_provider: ExecuteCommandOptions => with_execute_command_provider,
+    object workspace: WorkspaceOptions => with_workspace,
+    elective position_encoding: PositionEncodingKind => with_position_encoding,
+    elective experimental: Value => with_experimental,
+});
+
+impl ServerCapabilities {
+    /// Returns how the client should sync document changes.
+    pub fn sync_kind(&self) -> TextDocumentSyncKind {
+        self.text_document_sync.change()
+    }
 
-impl Default for TextDocumentSyncProvider {
-    fn default() -> Self {
-        TextDocumentSyncProvider::Kind(TextDocumentSyncKind::default())
+    /// Returns if the server provides hover support.
+    pub fn supports_hover(&self) -> bool {
+        self.hover_provider
     }
-}
 
-/// How the client should sync document changes with the server.
-#[lsp_kind(number)]
-pub enum TextDocumentSyncKind {
-    /// Documents should not be synced at all.
-    None = 0,
-    /// Documents are synced by always sending the full content of the document.
-    Full,
-    /// Documents are synced by sending incremental updates.
-    Incremental,
-}
+    /// Returns if the server provides completion support.
+    pub fn supports_completion(&self) -> bool {
+        !self.completion_provider.is_absent()
+    }
 
-impl Default for TextDocumentSyncKind {
-    fn default() -> Self {
-        TextDocumentSyncKind::None
+    /// Returns the characters that trigger completion automatically.
+    pub fn completion_trigger_characters(&self) -> &[String] {
+        self.completion_provider.as_option().map_or(&[], |options| &options.trigger_characters)
     }
-}
 
-/// Completion options.
-#[lsp_object(allow_missing, triggers = "completion", resolve_provider = "completion")]
-struct CompletionOptions {
-}
+    /// Returns if the server can resolve additional information for a completion item.
+    pub fn supports_completion_resolve(&self) -> bool {
+        self.completion_provider.as_option().is_some_and(|options| options.resolve_provider)
+    }
 
-/// Signature help options.
-#[lsp_object(allow_missing, triggers = "signature help")]
-struct SignatureHelpOptions {
-}
+    /// Returns if the server provides signature help support.
+    pub fn supports_signature_help(&self) -> bool {
+        !self.signature_help_provider.is_absent()
+    }
 
-#[lsp_object(document_selector, static_registration)]
-struct GotoOptions {
-}
+    /// Returns the characters that trigger signature help automatically.
+    pub fn signature_help_trigger_characters(&self) -> &[String] {
+        self.signature_help_provider.as_option().map_or(&[], |options| &options.trigger_characters)
+    }
 
-#[lsp_kind]
-#[serde(untagged)]
-enum BooleanOrOptions<T> {
-    Boolean(bool),
-    Options(T),
-}
+    /// Returns if the server provides goto declaration support.
+    pub fn supports_declaration(&self) -> bool {
+        self.declaration_provider.is_enabled()
+    }
 
-impl<T> Default for BooleanOrOptions<T> {
-    fn default() -> Self {
-        BooleanOrOptions::Boolean(false)
+    /// Returns if the server provides goto definition support.
+    pub fn supports_definition(&self) -> bool {
+        self.definition_provider
     }
-}
 
-/// Code Action options.
-#[lsp_object(allow_missing)]
-struct CodeActionOptions {
-    // TODO: Use CodeActionKind when available.
-    /// `CodeActionKind`s supported by server.
-    code_action_kinds: Vec<String>,
-}
+    /// Returns if the server provides goto type definition support.
+    pub fn supports_type_definition(&self) -> bool {
+        self.type_definition_provider.is_enabled()
+    }
 
-/// Code lens options.
-#[lsp_object(allow_missing, resolve_provider = "code lens")]
-struct CodeLensOptions {
-}
+    /// Returns if the server provides goto implementation support.
+    pub fn supports_implementation(&self) -> bool {
+        self.implementation_provider.is_enabled()
+    }
 
-/// Format document on type options.
-#[lsp_object]
-struct DocumentOnTypeFormattingOptions {
-    /// Character on which formatting should be triggered.
-    first_trigger_character: String,
-    /// More trigger characters.
-    #[serde(default)]
-    more_trigger_character: Vec<String>,
-}
+    /// Returns if the server provides find references support.
+    pub fn supports_references(&self) -> bool {
+        self.references_provider
+    }
 
-/// Rename options.
+    /// Returns if the server provides document highlight support.
+    pub fn supports_document_highlight(&self) -> bool {
+        self.document_highlight_provider
+    }
+
+    /// Returns if the server provides document symbol support.
+    pub fn supports_document_symbol(&self) -> bool {
+        self.document_symbol_provider
+    }
+
+    /// Returns if the server provides workspace symbol support.
+    pub fn supports_workspace_symbol(&self) -> bool {
+        self.workspace_symbol_provider
+    }
+
+    /// Returns if the server provides code actions.
+    pub fn supports_code_action(&self) -> bool {
+        self.code_action_provider.is_enabled()
+    }
+
+    /// Returns the `CodeActionKind`s the server may return.
+    ///
+    /// If empty, the server did not specify the kinds.
+    pub fn code_action_kinds(&self) -> &[CodeActionKind] {
+        self.code_action_provider.options().map_or(&[], |options| &options.code_action_kinds)
+    }
+
+    /// Returns if the server may return code actions of `kind`.
+    ///
+    /// If the server did not specify the kinds, it may return any kind.
+    pub fn supports_code_action_kind(&self, kind: &CodeActionKind) -> bool {
+        self.supports_code_action() && kind.matches(self.code_action_kinds())
+    }
+
+    /// Returns if the server can resolve additional information for a code action.
+    pub fn supports_code_action_resolve(&self) -> bool {
+        self.code_action_provider.options().is_some_and(|options| options.resolve_provider)
+    }
+
+    /// Returns if the server provides code lens.
+    pub fn supports_code_lens(&self) -> bool {
+        !self.code_lens_provider.is_absent()
+    }
+
+    /// Returns if the server can resolve additional information for a code lens.
+    pub fn supports_code_lens_resolve(&self) -> bool {
+        self.code_lens_provider.as_option().is_some_and(|options| options.resolve_provider)
+    }
+
+    /// Returns if the server provides document formatting.
+    pub fn supports_document_formatting(&self) -> bool {
+        self.document_formatting_provider
+    }
+
+    /// Returns if the server provides document range formatting.
+    pub fn supports_document_range_formatting(&self) -> bool {
+        self.document_range_formatting_provider
+    }
+
+    /// Returns the characters that trigger formatting on typing.
+    ///
+    /// If empty, the server does not provide document formatting on typing.
+    pub fn on_type_formatting_trigger_characters(&self) -> Vec<&str> {
+        self.document_on_type_formatting_provider.as_option().map_or_else(Vec::new, |options| {
+            let mut characters = vec![options.first_trigger_character.as_str()];
+
+            characters.extend(options.more_trigger_character.iter().map(String::as_str));
+            characters
+        })
+    }
+
+    /// Returns if the server provides rename support.
+    pub fn supports_rename(&self) -> bool {
+        self.rename_provider.is_enabled()
+    }
+
+    /// Returns if the server checks and tests renames before execution.
+    pub fn supports_rename_prepare(&self) -> bool {
+        self.rename_provider.options().is_some_and(|options| options.prepare_provider)
+    }
+
+    /// Returns if the server provides document link support.
+    pub fn supports_document_link(&self) -> bool {
+        !self.document_link_provider.is_absent()
+    }
+
+    /// Returns if the server can resolve additional information for a document link.
+    pub fn supports_document_link_resolve(&self) -> bool {
+        self.document_link_provider.as_option().is_some_and(|options| options.resolve_provider)
+    }
+
+    /// Returns if the server provides color provider support.
+    pub fn supports_color(&self) -> bool {
+        self.color_provider.is_enabled()
+    }
+
+    /// Returns if the server provides folding range support.
+    pub fn supports_folding_range(&self) -> bool {
+        self.folding_range_provider.is_enabled()
+    }
+
+    /// Returns the commands the server executes.
+    pub fn commands(&self) -> &[String] {
+        self.execute_command_provider.as_option().map_or(&[], |options| &options.commands)
+    }
+
+    /// Returns if the server supports workspace folders.
+    pub fn supports_workspace_folders(&self) -> bool {
+        self.workspace.workspace_folders.supported
+    }
+
+    /// Returns the position encoding used by the server.
+    pub fn encoding(&self) -> PositionEncodingKind {
+        self.position_encoding.as_option().cloned().unwrap_or_default()
+    }
+}
+
+#[lsp_kind]
+#[serde(untagged)]
+pub enum TextDocumentSyncProvider {
+    Options(TextDocumentSyncOptions),
+    Kind(TextDocumentSyncKind),
+}
+
+impl TextDocumentSyncProvider {
+    /// Returns how the client should sync document changes.
+    pub fn change(&self) -> TextDocumentSyncKind {
+        match self {
+            TextDocumentSyncProvider::Options(options) => options.change,
+            TextDocumentSyncProvider::Kind(kind) => *kind,
+        }
+    }
+
+    /// Returns the `TextDocumentSyncOptions` if provided.
+    pub fn options(&self) -> Option<&TextDocumentSyncOptions> {
+        match self {
+            TextDocumentSyncProvider::Options(options) => Some(options),
+            TextDocumentSyncProvider::Kind(_) => None,
+        }
+    }
+
+    /// Returns if the client should send open and close notifications.
+    ///
+    /// A bare `TextDocumentSyncKind` other than `TextDocumentSyncKind::None` implies open and
+    /// close notifications.
+    pub fn open_close(&self) -> bool {
+        match self {
+            TextDocumentSyncProvider::Options(options) => options.open_close,
+            TextDocumentSyncProvider::Kind(kind) => *kind != TextDocumentSyncKind::None,
+        }
+    }
+
+    /// Returns if the client should send will save notifications.
+    pub fn will_save(&self) -> bool {
+        self.options().is_some_and(|options| options.will_save)
+    }
+
+    /// Returns if the client should send will save wait until requests.
+    pub fn will_save_wait_until(&self) -> bool {
+        self.options().is_some_and(|options| options.will_save_wait_until)
+    }
+
+    /// Returns if the client should send save notifications.
+    ///
+    /// A bare `TextDocumentSyncKind` other than `TextDocumentSyncKind::None` implies save
+    /// notifications without the content of the document.
+    pub fn save(&self) -> bool {
+        match self {
+            TextDocumentSyncProvider::Options(options) => options.save.is_enabled(),
+            TextDocumentSyncProvider::Kind(kind) => *kind != TextDocumentSyncKind::None,
+        }
+    }
+
+    /// Returns if save notifications should include the content of the document.
+    pub fn include_text(&self) -> bool {
+        self.options()
+            .and_then(|options| options.save.options())
+            .is_some_and(|save| save.include_text)
+    }
+}
+
+impl Default for TextDocumentSyncProvider {
+    fn default() -> Self {
+        TextDocumentSyncProvider::Kind(TextDocumentSyncKind::default())
+    }
+}
+
+/// How the client should sync document changes with the server.
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum TextDocumentSyncKind {
+    /// Documents should not be synced at all.
+    #[default]
+    None = 0,
+    /// Documents are synced by always sending the full content of the document.
+    Full,
+    /// Documents are synced by sending incremental updates.
+    Incremental,
+}
+
+/// Completion options.
+#[lsp_object(allow_missing, triggers = "completion", resolve_provider = "completion")]
+pub struct CompletionOptions {
+}
+
+accessors!(CompletionOptions {
+    object trigger_characters: Vec<String> => with_trigger_characters,
+    value resolve_provider: bool => with_resolve_provider,
+});
+
+/// Signature help options.
+#[lsp_object(allow_missing, triggers = "signature help")]
+pub struct SignatureHelpOptions {
+}
+
+accessors!(SignatureHelpOptions {
+    object trigger_characters: Vec<String> => with_trigger_characters,
+});
+
+#[lsp_object(document_selector, static_registration)]
+pub struct GotoOptions {
+}
+
+accessors!(GotoOptions {
+    elective id: String => with_id,
+});
+
+#[lsp_kind]
+#[serde(untagged)]
+pub enum BooleanOrOptions<T> {
+    Boolean(bool),
+    Options(T),
+}
+
+impl<T> BooleanOrOptions<T> {
+    /// Returns if the capability is provided.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            BooleanOrOptions::Boolean(is_enabled) => *is_enabled,
+            BooleanOrOptions::Options(_) => true,
+        }
+    }
+
+    /// Returns the options if provided.
+    pub fn options(&self) -> Option<&T> {
+        match self {
+            BooleanOrOptions::Boolean(_) => None,
+            BooleanOrOptions::Options(options) => Some(options),
+        }
+    }
+}
+
+impl<T> Default for BooleanOrOptions<T> {
+    fn default() -> Self {
+        BooleanOrOptions::Boolean(false)
+    }
+}
+
+/// Code Action options.
+#[lsp_object(allow_missing, resolve_provider = "code action")]
+pub struct CodeActionOptions {
+    /// `CodeActionKind`s supported by server.
+    code_action_kinds: Vec<CodeActionKind>,
+}
+
+accessors!(CodeActionOptions {
+    object code_action_kinds: Vec<CodeActionKind> => with_code_action_kinds,
+    value resolve_provider: bool => with_resolve_provider,
+});
+
+/// Code lens options.
+#[lsp_object(allow_missing, resolve_provider = "code lens")]
+pub struct CodeLensOptions {
+}
+
+accessors!(CodeLensOptions {
+    value resolve_provider: bool => with_resolve_provider,
+});
+
+/// Format document on type options.
+#[lsp_object]
+pub struct DocumentOnTypeFormattingOptions {
+    /// Character on which formatting should be triggered.
+    first_trigger_character: String,
+    /// More trigger characters.
+    #[serde(default)]
+    more_trigger_character: Vec<String>,
+}
+
+accessors!(DocumentOnTypeFormattingOptions {
+    object first_trigger_character: String => with_first_trigger_character,
+    object more_trigger_character: Vec<String> => with_more_trigger_character,
+});
+
+/// Rename options.
 #[lsp_object(allow_missing)]
-struct RenameOptions {
+pub struct RenameOptions {
     /// Renames should be checked and tested before being executed.
     prepare_provider: bool,
 }
 
+accessors!(RenameOptions {
+    value prepare_provider: bool => with_prepare_provider,
+});
+
 /// Document link options.
 #[lsp_object(allow_missing, resolve_provider = "document links")]
-struct DocumentLinkOptions {
+pub struct DocumentLinkOptions {
 }
 
+accessors!(DocumentLinkOptions {
+    value resolve_provider: bool => with_resolve_provider,
+});
+
 #[lsp_object(document_selector, static_registration)]
-struct StaticDocumentSelectorOptions<T> {
+pub struct StaticDocumentSelectorOptions<T> {
     options: T,
 }
 
+impl<T> StaticDocumentSelectorOptions<T> {
+    pub fn new(options: T) -> Self {
+        StaticDocumentSelectorOptions {
+            document_selector: None,
+            id: Elective::Absent,
+            options,
+        }
+    }
+
+    /// Sets the id used to register the request.
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Elective::Present(id);
+        self
+    }
+
+    /// Returns the options.
+    pub fn options(&self) -> &T {
+        &self.options
+    }
+}
+
 /// Color provider options.
 #[lsp_object]
-struct ColorProviderOptions {
+pub struct ColorProviderOptions {
 }
 
 /// Folding range provider options.
 #[lsp_object]
-struct FoldingRangeProviderOptions {
+pub struct FoldingRangeProviderOptions {
 }
 
 #[lsp_kind]
 #[serde(untagged)]
-enum BooleanOrOptionsOrStaticDocumentSelectorOptions<T> {
+pub enum BooleanOrOptionsOrStaticDocumentSelectorOptions<T> {
     Boolean(bool),
     Options(T),
     StaticDocumentSelectorOptions(StaticDocumentSelectorOptions<T>),
 }
 
+impl<T> BooleanOrOptionsOrStaticDocumentSelectorOptions<T> {
+    /// Returns if the capability is provided.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            BooleanOrOptionsOrStaticDocumentSelectorOptions::Boolean(is_enabled) => *is_enabled,
+            _ => true,
+        }
+    }
+
+    /// Returns the options if provided.
+    pub fn options(&self) -> Option<&T> {
+        match self {
+            BooleanOrOptionsOrStaticDocumentSelectorOptions::Boolean(_) => None,
+            BooleanOrOptionsOrStaticDocumentSelectorOptions::Options(options) => Some(options),
+            BooleanOrOptionsOrStaticDocumentSelectorOptions::StaticDocumentSelectorOptions(selector_options) => {
+                Some(&selector_options.options)
+            }
+        }
+    }
+}
+
 impl<T> Default for BooleanOrOptionsOrStaticDocumentSelectorOptions<T> {
     fn default() -> Self {
         BooleanOrOptionsOrStaticDocumentSelectorOptions::Boolean(false)
@@ -684,18 +1767,26 @@ impl<T> Default for BooleanOrOptionsOrStaticDocumentSelectorOptions<T> {
 
 /// Execute command options.
 #[lsp_object]
-struct ExecuteCommandOptions {
+pub struct ExecuteCommandOptions {
     /// Commands to be executed on the server.
     commands: Vec<String>,
 }
 
+accessors!(ExecuteCommandOptions {
+    object commands: Vec<String> => with_commands,
+});
+
 /// Describes server capabilities specific to the workspace.
 #[lsp_object(allow_missing)]
-struct WorkspaceOptions {
+pub struct WorkspaceOptions {
     /// Capabilities specific to workspace folders.
     workspace_folders: WorkspaceFoldersOptions,
 }
 
+accessors!(WorkspaceOptions {
+    object workspace_folders: WorkspaceFoldersOptions => with_workspace_folders,
+});
+
 #[lsp_object(allow_missing)]
 pub struct TextDocumentSyncOptions {
     /// Client sends open and close notifications to server.
@@ -707,28 +1798,45 @@ pub struct TextDocumentSyncOptions {
     /// Client sends will save wait until notifications to server.
     will_save_wait_until: bool,
     /// Client sends save notifications to server.
-    save: SaveOptions,
+    save: BooleanOrOptions<SaveOptions>,
 }
 
+accessors!(TextDocumentSyncOptions {
+    value open_close: bool => with_open_close,
+    value change: TextDocumentSyncKind => with_change,
+    value will_save: bool => with_will_save,
+    value will_save_wait_until: bool => with_will_save_wait_until,
+    object save: BooleanOrOptions<SaveOptions> => with_save,
+});
+
 /// Describes server capabilities specific to `WorkspaceFolder`s.
 #[lsp_object(allow_missing)]
-struct WorkspaceFoldersOptions {
+pub struct WorkspaceFoldersOptions {
     /// Supports workspace folders.
     supported: bool,
     /// Supports `WorkspaceFolder` change notifications.
     change_notifications: ChangeNotificationsOptions,
 }
 
+accessors!(WorkspaceFoldersOptions {
+    value supported: bool => with_supported,
+    object change_notifications: ChangeNotificationsOptions => with_change_notifications,
+});
+
 /// Save options.
 #[lsp_object(allow_missing)]
-struct SaveOptions {
+pub struct SaveOptions {
     /// Content is included in save notifications.
     include_text: bool,
 }
 
+accessors!(SaveOptions {
+    value include_text: bool => with_include_text,
+});
+
 #[lsp_kind]
 #[serde(untagged)]
-enum ChangeNotificationsOptions {
+pub enum ChangeNotificationsOptions {
     Boolean(bool),
     Id(String),
 }
@@ -739,11 +1847,45 @@ impl Default for ChangeNotificationsOptions {
     }
 }
 
+/// The `initialized` notification.
+pub enum Initialized {}
+
+impl Notification for Initialized {
+    type Params = InitializedParams;
+    const METHOD: &'static str = "initialized";
+}
+
 /// Notification sent from client to server after client receives `InitializeResult`.
 #[lsp_object]
 pub struct InitializedParams {
 }
 
+/// The `shutdown` request.
+pub enum Shutdown {}
+
+impl Request for Shutdown {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "shutdown";
+}
+
+/// The `exit` notification.
+pub enum Exit {}
+
+impl Notification for Exit {
+    type Params = ();
+    const METHOD: &'static str = "exit";
+}
+
+/// The `client/registerCapability` request.
+pub enum RegisterCapability {}
+
+impl Request for RegisterCapability {
+    type Params = RegistrationParams;
+    type Result = ();
+    const METHOD: &'static str = "client/registerCapability";
+}
+
 /// Request sent from server to client to register for a new capability on the client side.
 #[lsp_object]
 pub struct RegistrationParams {
@@ -766,6 +1908,14 @@ pub struct Registration {
 struct RegistrationResult {
 }
 
+/// The `textDocument/didOpen` notification.
+pub enum DidOpenTextDocument {}
+
+impl Notification for DidOpenTextDocument {
+    type Params = DidOpenTextDocumentParams;
+    const METHOD: &'static str = "textDocument/didOpen";
+}
+
 /// Notification sent from the client to server to signal newly opened text documents.
 #[lsp_object]
 pub struct DidOpenTextDocumentParams {
@@ -784,10 +1934,9 @@ impl From<TextDocumentItem> for DidOpenTextDocumentParams {
 #[derive(Clone)]
 pub struct TextDocumentItem {
     /// URI of text document.
-    pub uri: String,
-    // TODO: Add language identifier enum.
+    pub uri: DocumentUri,
     /// Language identifier of text document.
-    pub language_id: String,
+    pub language_id: LanguageId,
     /// Version number of text document.
     pub version: i64,
     /// Content of the text document.
@@ -800,6 +1949,393 @@ impl TextDocumentItem {
     }
 }
 
+/// Identifies the language of a text document.
+///
+/// `LanguageId::Other` holds an identifier that is not known by the specification. The default is
+/// `LanguageId::PlainText`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LanguageId {
+    /// ABAP.
+    Abap,
+    /// Windows batch.
+    Bat,
+    /// BibTeX.
+    BibTeX,
+    /// Clojure.
+    Clojure,
+    /// CoffeeScript.
+    CoffeeScript,
+    /// C.
+    C,
+    /// C++.
+    Cpp,
+    /// C#.
+    CSharp,
+    /// CSS.
+    Css,
+    /// Diff.
+    Diff,
+    /// Dart.
+    Dart,
+    /// Dockerfile.
+    Dockerfile,
+    /// Elixir.
+    Elixir,
+    /// Erlang.
+    Erlang,
+    /// F#.
+    FSharp,
+    /// Git commit message.
+    GitCommit,
+    /// Git rebase todo list.
+    GitRebase,
+    /// Go.
+    Go,
+    /// Groovy.
+    Groovy,
+    /// Handlebars.
+    Handlebars,
+    /// HTML.
+    Html,
+    /// Ini.
+    Ini,
+    /// Java.
+    Java,
+    /// JavaScript.
+    JavaScript,
+    /// JavaScript React.
+    JavaScriptReact,
+    /// JSON.
+    Json,
+    /// LaTeX.
+    LaTeX,
+    /// Less.
+    Less,
+    /// Lua.
+    Lua,
+    /// Makefile.
+    Makefile,
+    /// Markdown.
+    Markdown,
+    /// Objective-C.
+    ObjectiveC,
+    /// Objective-C++.
+    ObjectiveCpp,
+    /// Perl.
+    Perl,
+    /// Perl 6.
+    Perl6,
+    /// PHP.
+    Php,
+    /// Plain text.
+    #[default]
+    PlainText,
+    /// PowerShell.
+    PowerShell,
+    /// Pug.
+    Pug,
+    /// Python.
+    Python,
+    /// R.
+    R,
+    /// Razor.
+    Razor,
+    /// Ruby.
+    Ruby,
+    /// Rust.
+    Rust,
+    /// SCSS.
+    Scss,
+    /// Sass.
+    Sass,
+    /// Scala.
+    Scala,
+    /// ShaderLab.
+    ShaderLab,
+    /// Shell script.
+    ShellScript,
+    /// SQL.
+    Sql,
+    /// Swift.
+    Swift,
+    /// TypeScript.
+    TypeScript,
+    /// TypeScript React.
+    TypeScriptReact,
+    /// TeX.
+    TeX,
+    /// Visual Basic.
+    VisualBasic,
+    /// XML.
+    Xml,
+    /// XSL.
+    Xsl,
+    /// YAML.
+    Yaml,
+    /// A language without a well-known identifier.
+    Other(String),
+}
+
+/// The `LanguageId`s with well-known identifiers.
+const KNOWN_LANGUAGE_IDS: [LanguageId; 58] = [
+    LanguageId::Abap,
+    LanguageId::Bat,
+    LanguageId::BibTeX,
+    LanguageId::Clojure,
+    LanguageId::CoffeeScript,
+    LanguageId::C,
+    LanguageId::Cpp,
+    LanguageId::CSharp,
+    LanguageId::Css,
+    LanguageId::Diff,
+    LanguageId::Dart,
+    LanguageId::Dockerfile,
+    LanguageId::Elixir,
+    LanguageId::Erlang,
+    LanguageId::FSharp,
+    LanguageId::GitCommit,
+    LanguageId::GitRebase,
+    LanguageId::Go,
+    LanguageId::Groovy,
+    LanguageId::Handlebars,
+    LanguageId::Html,
+    LanguageId::Ini,
+    LanguageId::Java,
+    LanguageId::JavaScript,
+    LanguageId::JavaScriptReact,
+    LanguageId::Json,
+    LanguageId::LaTeX,
+    LanguageId::Less,
+    LanguageId::Lua,
+    LanguageId::Makefile,
+    LanguageId::Markdown,
+    LanguageId::ObjectiveC,
+    LanguageId::ObjectiveCpp,
+    LanguageId::Perl,
+    LanguageId::Perl6,
+    LanguageId::Php,
+    LanguageId::PlainText,
+    LanguageId::PowerShell,
+    LanguageId::Pug,
+    LanguageId::Python,
+    LanguageId::R,
+    LanguageId::Razor,
+    LanguageId::Ruby,
+    LanguageId::Rust,
+    LanguageId::Scss,
+    LanguageId::Sass,
+    LanguageId::Scala,
+    LanguageId::ShaderLab,
+    LanguageId::ShellScript,
+    LanguageId::Sql,
+    LanguageId::Swift,
+    LanguageId::TypeScript,
+    LanguageId::TypeScriptReact,
+    LanguageId::TeX,
+    LanguageId::VisualBasic,
+    LanguageId::Xml,
+    LanguageId::Xsl,
+    LanguageId::Yaml,
+];
+
+impl LanguageId {
+    /// Returns the identifier of the language.
+    pub fn as_str(&self) -> &str {
+        match self {
+            LanguageId::Abap => "abap",
+            LanguageId::Bat => "bat",
+            LanguageId::BibTeX => "bibtex",
+            LanguageId::Clojure => "clojure",
+            LanguageId::CoffeeScript => "coffeescript",
+            LanguageId::C => "c",
+            LanguageId::Cpp => "cpp",
+            LanguageId::CSharp => "csharp",
+            LanguageId::Css => "css",
+            LanguageId::Diff => "diff",
+            LanguageId::Dart => "dart",
+            LanguageId::Dockerfile => "dockerfile",
+            LanguageId::Elixir => "elixir",
+            LanguageId::Erlang => "erlang",
+            LanguageId::FSharp => "fsharp",
+            LanguageId::GitCommit => "git-commit",
+            LanguageId::GitRebase => "git-rebase",
+            LanguageId::Go => "go",
+            LanguageId::Groovy => "groovy",
+            LanguageId::Handlebars => "handlebars",
+            LanguageId::Html => "html",
+            LanguageId::Ini => "ini",
+            LanguageId::Java => "java",
+            LanguageId::JavaScript => "javascript",
+            LanguageId::JavaScriptReact => "javascriptreact",
+            LanguageId::Json => "json",
+            LanguageId::LaTeX => "latex",
+            LanguageId::Less => "less",
+            LanguageId::Lua => "lua",
+            LanguageId::Makefile => "makefile",
+            LanguageId::Markdown => "markdown",
+            LanguageId::ObjectiveC => "objective-c",
+            LanguageId::ObjectiveCpp => "objective-cpp",
+            LanguageId::Perl => "perl",
+            LanguageId::Perl6 => "perl6",
+            LanguageId::Php => "php",
+            LanguageId::PlainText => "plaintext",
+            LanguageId::PowerShell => "powershell",
+            LanguageId::Pug => "jade",
+            LanguageId::Python => "python",
+            LanguageId::R => "r",
+            LanguageId::Razor => "razor",
+            LanguageId::Ruby => "ruby",
+            LanguageId::Rust => "rust",
+            LanguageId::Scss => "scss",
+            LanguageId::Sass => "sass",
+            LanguageId::Scala => "scala",
+            LanguageId::ShaderLab => "shaderlab",
+            LanguageId::ShellScript => "shellscript",
+            LanguageId::Sql => "sql",
+            LanguageId::Swift => "swift",
+            LanguageId::TypeScript => "typescript",
+            LanguageId::TypeScriptReact => "typescriptreact",
+            LanguageId::TeX => "tex",
+            LanguageId::VisualBasic => "vb",
+            LanguageId::Xml => "xml",
+            LanguageId::Xsl => "xsl",
+            LanguageId::Yaml => "yaml",
+            LanguageId::Other(id) => id,
+        }
+    }
+
+    /// Returns the language of the file at `path`, detected from its name or extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let file_name = path.file_name()?.to_str()?;
+
+        LanguageId::from_file_name(file_name).or_else(|| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(LanguageId::from_extension)
+        })
+    }
+
+    /// Returns the language of a file named `file_name` that is not detected by its extension.
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        Some(match file_name {
+            "Makefile" | "makefile" | "GNUmakefile" => LanguageId::Makefile,
+            "Dockerfile" | "Containerfile" => LanguageId::Dockerfile,
+            "COMMIT_EDITMSG" | "MERGE_MSG" | "TAG_EDITMSG" => LanguageId::GitCommit,
+            "git-rebase-todo" => LanguageId::GitRebase,
+            "Gemfile" | "Rakefile" => LanguageId::Ruby,
+            ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" => LanguageId::ShellScript,
+            "Cargo.lock" => LanguageId::Other("toml".to_string()),
+            _ => return None,
+        })
+    }
+
+    /// Returns the language of a file with `extension`, ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension.to_ascii_lowercase().as_str() {
+            "abap" => LanguageId::Abap,
+            "bat" | "cmd" => LanguageId::Bat,
+            "bib" => LanguageId::BibTeX,
+            "clj" | "cljc" | "cljs" | "edn" => LanguageId::Clojure,
+            "coffee" => LanguageId::CoffeeScript,
+            "c" | "h" => LanguageId::C,
+            "cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" | "h++" => LanguageId::Cpp,
+            "cs" => LanguageId::CSharp,
+            "css" => LanguageId::Css,
+            "diff" | "patch" => LanguageId::Diff,
+            "dart" => LanguageId::Dart,
+            "dockerfile" => LanguageId::Dockerfile,
+            "ex" | "exs" => LanguageId::Elixir,
+            "erl" | "hrl" => LanguageId::Erlang,
+            "fs" | "fsi" | "fsx" => LanguageId::FSharp,
+            "go" => LanguageId::Go,
+            "gradle" | "groovy" => LanguageId::Groovy,
+            "handlebars" | "hbs" => LanguageId::Handlebars,
+            "htm" | "html" => LanguageId::Html,
+            "ini" => LanguageId::Ini,
+            "java" => LanguageId::Java,
+            "cjs" | "js" | "mjs" => LanguageId::JavaScript,
+            "jsx" => LanguageId::JavaScriptReact,
+            "json" => LanguageId::Json,
+            "cls" | "ltx" | "sty" | "tex" => LanguageId::LaTeX,
+            "less" => LanguageId::Less,
+            "lua" => LanguageId::Lua,
+            "mak" | "mk" => LanguageId::Makefile,
+            "markdown" | "md" => LanguageId::Markdown,
+            "m" => LanguageId::ObjectiveC,
+            "mm" => LanguageId::ObjectiveCpp,
+            "pl" | "pm" => LanguageId::Perl,
+            "p6" | "pm6" | "raku" | "rakumod" => LanguageId::Perl6,
+            "php" => LanguageId::Php,
+            "txt" => LanguageId::PlainText,
+            "ps1" | "psd1" | "psm1" => LanguageId::PowerShell,
+            "jade" | "pug" => LanguageId::Pug,
+            "py" | "pyi" | "pyw" => LanguageId::Python,
+            "r" => LanguageId::R,
+            "cshtml" | "razor" => LanguageId::Razor,
+            "rb" => LanguageId::Ruby,
+            "rs" => LanguageId::Rust,
+            "scss" => LanguageId::Scss,
+            "sass" => LanguageId::Sass,
+            "sc" | "scala" => LanguageId::Scala,
+            "shader" => LanguageId::ShaderLab,
+            "bash" | "sh" | "zsh" => LanguageId::ShellScript,
+            "sql" => LanguageId::Sql,
+            "swift" => LanguageId::Swift,
+            "cts" | "mts" | "ts" => LanguageId::TypeScript,
+            "tsx" => LanguageId::TypeScriptReact,
+            "vb" => LanguageId::VisualBasic,
+            "xml" | "xsd" => LanguageId::Xml,
+            "xsl" | "xslt" => LanguageId::Xsl,
+            "yaml" | "yml" => LanguageId::Yaml,
+            "toml" => LanguageId::Other("toml".to_string()),
+            _ => return None,
+        })
+    }
+}
+
+impl Display for LanguageId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for LanguageId {
+    fn from(value: &str) -> Self {
+        KNOWN_LANGUAGE_IDS
+            .iter()
+            .find(|language_id| language_id.as_str() == value)
+            .cloned()
+            .unwrap_or_else(|| LanguageId::Other(value.to_string()))
+    }
+}
+
+impl From<String> for LanguageId {
+    fn from(value: String) -> Self {
+        LanguageId::from(value.as_str())
+    }
+}
+
+impl Serialize for LanguageId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LanguageId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(LanguageId::from)
+    }
+}
+
+/// The `textDocument/didChange` notification.
+pub enum DidChangeTextDocument {}
+
+impl Notification for DidChangeTextDocument {
+    type Params = DidChangeTextDocumentParams;
+    const METHOD: &'static str = "textDocument/didChange";
+}
+
 /// Notification sent from client to server to signal changes to a text document.
 #[lsp_object]
 pub struct DidChangeTextDocumentParams {
@@ -818,17 +2354,28 @@ impl DidChangeTextDocumentParams {
             content_changes,
         }
     }
+
+    /// Returns the document that changed.
+    pub fn text_document(&self) -> &VersionedTextDocumentIdentifier {
+        &self.text_document
+    }
+
+    /// Returns the changes to the content.
+    pub fn content_changes(&self) -> &[TextDocumentContentChangeEvent] {
+        &self.content_changes
+    }
 }
 
 /// Denotes a specific version of a text document.
 #[lsp_object]
+#[derive(Clone)]
 pub struct VersionedTextDocumentIdentifier {
     /// URI of text document.
-    uri: String,
+    pub uri: DocumentUri,
     /// Version number of the document.
     ///
     /// If `Option::None`, content on disk is the truth.
-    version: Option<i64>,
+    pub version: Option<i64>,
 }
 
 impl From<TextDocumentItem> for VersionedTextDocumentIdentifier {
@@ -862,43 +2409,267 @@ impl TextDocumentContentChangeEvent {
             text,
         }
     }
-}
 
-/// Start and end `Position`s where the end `Position` is exclusive.
-#[lsp_object]
-#[derive(Clone, Copy, Eq, PartialEq)]
-pub struct Range {
-    /// Start `Position` of the `Range`.
-    pub start: Position,
-    /// End `Position` of the `Range`.
-    pub end: Position,
-}
+    /// Creates a `TextDocumentContentChangeEvent` that replaces the full content with `text`.
+    pub fn full(text: String) -> Self {
+        TextDocumentContentChangeEvent {
+            range: Elective::Absent,
+            range_length: Elective::Absent,
+            text,
+        }
+    }
 
-impl Range {
-    pub fn with_line(line: u64) -> Self {
-        Range::with_partial_line(line, 0, u64::max_value())
+    /// Returns the `Range` of the changed document.
+    ///
+    /// Returns `Option::None` if `text` is the full content of the document.
+    pub fn range(&self) -> Option<&Range> {
+        self.range.as_option()
     }
 
-    pub fn with_partial_line(line: u64, start: u64, end: u64) -> Self {
-        Range {
-            start: Position { line, character: start },
-            end: Position { line, character: end },
-        }
+    /// Returns the new text.
+    pub fn text(&self) -> &str {
+        &self.text
     }
 }
 
-impl From<Position> for Range {
-    fn from(value: Position) -> Self {
-        Range {
-            start: value,
+/// The `textDocument/willSave` notification.
+pub enum WillSaveTextDocument {}
+
+impl Notification for WillSaveTextDocument {
+    type Params = WillSaveTextDocumentParams;
+    const METHOD: &'static str = "textDocument/willSave";
+}
+
+/// Notification sent from client to server before a text document is saved.
+#[lsp_object]
+pub struct WillSaveTextDocumentParams {
+    /// Document that will be saved.
+    pub text_document: TextDocumentIdentifier,
+    /// Reason the document is saved.
+    pub reason: TextDocumentSaveReason,
+}
+
+/// Represents reasons why a text document is saved.
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum TextDocumentSaveReason {
+    /// Manually triggered by the user.
+    #[default]
+    Manual = 1,
+    /// Automatically triggered after a delay.
+    AfterDelay,
+    /// Triggered when the editor lost focus.
+    FocusOut,
+}
+
+/// The `textDocument/willSaveWaitUntil` request.
+///
+/// The server may respond with edits to apply before the document is saved.
+pub enum WillSaveWaitUntilTextDocument {}
+
+impl Request for WillSaveWaitUntilTextDocument {
+    type Params = WillSaveTextDocumentParams;
+    type Result = Option<Vec<TextEdit>>;
+    const METHOD: &'static str = "textDocument/willSaveWaitUntil";
+}
+
+/// The `textDocument/didSave` notification.
+pub enum DidSaveTextDocument {}
+
+impl Notification for DidSaveTextDocument {
+    type Params = DidSaveTextDocumentParams;
+    const METHOD: &'static str = "textDocument/didSave";
+}
+
+/// Notification sent from client to server after a text document is saved.
+#[lsp_object]
+pub struct DidSaveTextDocumentParams {
+    /// Document that was saved.
+    pub text_document: TextDocumentIdentifier,
+    /// Content of the document when saved.
+    ///
+    /// Present only if the server requested it with `SaveOptions::include_text`.
+    pub text: Elective<String>,
+}
+
+/// The `textDocument/didClose` notification.
+pub enum DidCloseTextDocument {}
+
+impl Notification for DidCloseTextDocument {
+    type Params = DidCloseTextDocumentParams;
+    const METHOD: &'static str = "textDocument/didClose";
+}
+
+/// Notification sent from client to server after a text document is closed.
+#[lsp_object]
+pub struct DidCloseTextDocumentParams {
+    /// Document that was closed.
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Start and end `Position`s where the end `Position` is exclusive.
+#[lsp_object]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Range {
+    /// Start `Position` of the `Range`.
+    pub start: Position,
+    /// End `Position` of the `Range`.
+    pub end: Position,
+}
+
+impl Range {
+    pub fn new(start: Position, end: Position) -> Self {
+        Range { start, end }
+    }
+
+    pub fn with_line(line: u64) -> Self {
+        Range::with_partial_line(line, 0, u64::MAX)
+    }
+
+    pub fn with_partial_line(line: u64, start: u64, end: u64) -> Self {
+        Range {
+            start: Position { line, character: start },
+            end: Position { line, character: end },
+        }
+    }
+
+    /// Returns if the `Range` contains no characters.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns if `start` is after `end`.
+    pub fn is_reversed(&self) -> bool {
+        self.start > self.end
+    }
+
+    /// Returns the `Range` with `start` and `end` swapped if it is reversed.
+    pub fn normalized(self) -> Self {
+        if self.is_reversed() {
+            Range {
+                start: self.end,
+                end: self.start,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Returns if `position` is within the `Range`.
+    ///
+    /// Since `end` is exclusive, an empty `Range` contains no `Position`.
+    pub fn contains(&self, position: Position) -> bool {
+        let range = self.normalized();
+
+        range.start <= position && position < range.end
+    }
+
+    /// Returns if `other` is within the `Range`.
+    pub fn contains_range(&self, other: &Range) -> bool {
+        let (range, other) = (self.normalized(), other.normalized());
+
+        range.start <= other.start && other.end <= range.end
+    }
+
+    /// Returns if the `Range` and `other` overlap.
+    ///
+    /// `Range`s that only touch do not intersect. As with `Range::contains`, an empty `Range`
+    /// intersects no `Range`.
+    pub fn intersects(&self, other: &Range) -> bool {
+        let (range, other) = (self.normalized(), other.normalized());
+
+        !range.is_empty() && !other.is_empty() && range.start < other.end && other.start < range.end
+    }
+
+    /// Returns the smallest `Range` that contains both the `Range` and `other`.
+    pub fn union(&self, other: &Range) -> Self {
+        let (range, other) = (self.normalized(), other.normalized());
+
+        Range {
+            start: range.start.min(other.start),
+            end: range.end.max(other.end),
+        }
+    }
+
+    /// Returns the `Range` after `edits` are applied to the document.
+    ///
+    /// `start` and `end` are translated as by `Position::translate`.
+    pub fn translate(self, edits: &[TextEdit]) -> Self {
+        self.translate_with(edits, Stickiness::After, Stickiness::After, &PositionEncodingKind::Utf16)
+    }
+
+    /// Returns the `Range` after `edits` are applied to the document, with `start` and `end`
+    /// translated as by `Position::translate_with` with `start_stickiness` and `end_stickiness`.
+    ///
+    /// If the translated `Range` would be reversed, it is collapsed to its start.
+    pub fn translate_with(
+        self,
+        edits: &[TextEdit],
+        start_stickiness: Stickiness,
+        end_stickiness: Stickiness,
+        encoding: &PositionEncodingKind,
+    ) -> Self {
+        let range = self.normalized();
+
+        Range::collapsed(
+            range.start.translate_with(edits, start_stickiness, encoding),
+            range.end.translate_with(edits, end_stickiness, encoding),
+        )
+    }
+
+    /// Returns the `Range` after `changes` are applied to the document in order, with `start`
+    /// and `end` translated as by `Position::translate_changes`.
+    ///
+    /// Returns `Option::None` if a change replaces the full content of the document.
+    pub fn translate_changes(
+        self,
+        changes: &[TextDocumentContentChangeEvent],
+        start_stickiness: Stickiness,
+        end_stickiness: Stickiness,
+        encoding: &PositionEncodingKind,
+    ) -> Option<Self> {
+        let range = self.normalized();
+
+        Some(Range::collapsed(
+            range.start.translate_changes(changes, start_stickiness, encoding)?,
+            range.end.translate_changes(changes, end_stickiness, encoding)?,
+        ))
+    }
+
+    /// Returns the `Range` from `start` to `end`, or the empty `Range` at `start` if `end` is
+    /// before `start`.
+    fn collapsed(start: Position, end: Position) -> Self {
+        Range {
+            start,
+            end: end.max(start),
+        }
+    }
+}
+
+impl From<Position> for Range {
+    fn from(value: Position) -> Self {
+        Range {
+            start: value,
             end: value,
         }
     }
 }
 
+/// Where a `Position` moves when the text around it is replaced.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Stickiness {
+    /// Stays before the new text.
+    Before,
+    /// Moves after the new text.
+    #[default]
+    After,
+}
+
 /// A line and character offset of a text document.
+///
+/// `Position`s are ordered by line and then by character.
 #[lsp_object]
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Position {
     /// Zero-based index of the line.
     pub line: u64,
@@ -910,20 +2681,64 @@ pub struct Position {
 }
 
 impl Position {
+    pub fn new(line: u64, character: u64) -> Self {
+        Position { line, character }
+    }
+
+    /// Moves up a line, staying on the first line.
     pub fn move_up(&mut self) {
-        self.line -= 1;
+        self.line = self.line.saturating_sub(1);
+    }
+
+    /// Moves down a line, staying on the last representable line.
+    pub fn move_down(&mut self) {
+        self.line = self.line.saturating_add(1);
     }
 
     pub fn move_to_end_of_line(&mut self) {
-        self.character = u64::max_value();
+        self.character = u64::MAX;
     }
 
+    /// Moves left a character, staying on the first character.
     pub fn move_left(&mut self) {
-        self.character -= 1;
+        self.character = self.character.saturating_sub(1);
     }
 
+    /// Moves right a character, staying on the last representable character.
     pub fn move_right(&mut self) {
-        self.character += 1;
+        self.character = self.character.saturating_add(1);
+    }
+
+    /// Returns the `Position` a line up, or `Option::None` if on the first line.
+    pub fn checked_up(self) -> Option<Self> {
+        Some(Position {
+            line: self.line.checked_sub(1)?,
+            ..self
+        })
+    }
+
+    /// Returns the `Position` a line down, or `Option::None` if the line overflows.
+    pub fn checked_down(self) -> Option<Self> {
+        Some(Position {
+            line: self.line.checked_add(1)?,
+            ..self
+        })
+    }
+
+    /// Returns the `Position` a character left, or `Option::None` if on the first character.
+    pub fn checked_left(self) -> Option<Self> {
+        Some(Position {
+            character: self.character.checked_sub(1)?,
+            ..self
+        })
+    }
+
+    /// Returns the `Position` a character right, or `Option::None` if the character overflows.
+    pub fn checked_right(self) -> Option<Self> {
+        Some(Position {
+            character: self.character.checked_add(1)?,
+            ..self
+        })
     }
 
     pub fn is_first_character(&self) -> bool {
@@ -933,64 +2748,1279 @@ impl Position {
     pub fn is_first_line(&self) -> bool {
         self.line == 0
     }
+
+    /// Returns the `Position` after `edits` are applied to the document.
+    ///
+    /// Equivalent to `Position::translate_with` with `Stickiness::After` and
+    /// `PositionEncodingKind::Utf16`, so a `Position` at an insertion or within a replaced `Range`
+    /// moves to the end of the new text.
+    pub fn translate(self, edits: &[TextEdit]) -> Self {
+        self.translate_with(edits, Stickiness::After, &PositionEncodingKind::Utf16)
+    }
+
+    /// Returns the `Position` after `edits` are applied to the document.
+    ///
+    /// As with `workspace/applyEdit`, the `Range`s of `edits` refer to the document before any
+    /// are applied and must not overlap. A `Position` before or at the start of a replaced
+    /// `Range` is kept and a `Position` at or after its end moves with the text that follows.
+    /// `stickiness` decides where a `Position` at an insertion or within a replaced `Range`
+    /// moves. `Position::character` is in code units of `encoding`.
+    pub fn translate_with(self, edits: &[TextEdit], stickiness: Stickiness, encoding: &PositionEncodingKind) -> Self {
+        let mut edits = edits.iter().collect::<Vec<_>>();
+        let mut position = self;
+
+        // Translating through the last edit first keeps the `Range`s of earlier edits valid.
+        edits.sort_by_key(|edit| edit.range.start);
+
+        for edit in edits.into_iter().rev() {
+            position = position.translate_edit(edit.range, &edit.new_text, stickiness, encoding);
+        }
+
+        position
+    }
+
+    /// Returns the `Position` after `changes` are applied to the document in order.
+    ///
+    /// Each change is translated as by `Position::translate_with`. Returns `Option::None` if a
+    /// change replaces the full content of the document.
+    pub fn translate_changes(
+        self,
+        changes: &[TextDocumentContentChangeEvent],
+        stickiness: Stickiness,
+        encoding: &PositionEncodingKind,
+    ) -> Option<Self> {
+        changes.iter().try_fold(self, |position, change| {
+            Some(position.translate_edit(*change.range()?, change.text(), stickiness, encoding))
+        })
+    }
+
+    /// Returns the `Position` after `range` is replaced by `new_text`.
+    fn translate_edit(
+        self,
+        range: Range,
+        new_text: &str,
+        stickiness: Stickiness,
+        encoding: &PositionEncodingKind,
+    ) -> Self {
+        let range = range.normalized();
+
+        if self < range.start || (self == range.start && (self < range.end || stickiness == Stickiness::Before)) {
+            return self;
+        }
+
+        let new_end = range.start.advance(new_text, encoding);
+
+        if self < range.end {
+            return match stickiness {
+                Stickiness::Before => range.start,
+                Stickiness::After => new_end,
+            };
+        }
+
+        if self.line == range.end.line {
+            Position {
+                line: new_end.line,
+                character: new_end.character.saturating_add(self.character - range.end.character),
+            }
+        } else {
+            Position {
+                line: self.line - range.end.line + new_end.line,
+                character: self.character,
+            }
+        }
+    }
+
+    /// Returns the `Position` at the end of `text` inserted at the `Position`.
+    ///
+    /// `Position::character` is in code units of `encoding`.
+    fn advance(self, text: &str, encoding: &PositionEncodingKind) -> Self {
+        let mut position = self;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if chars.peek() == Some(&'\n') => {}
+                '\n' | '\r' => {
+                    position.line += 1;
+                    position.character = 0;
+                }
+                c => position.character += encoding.len(c),
+            }
+        }
+
+        position
+    }
+}
+
+/// The `textDocument/publishDiagnostics` notification.
+pub enum PublishDiagnostics {}
+
+impl Notification for PublishDiagnostics {
+    type Params = PublishDiagnosticsParams;
+    const METHOD: &'static str = "textDocument/publishDiagnostics";
 }
 
 /// Notification sent from the server to the client to signal results of validation runs.
 #[lsp_object]
 pub struct PublishDiagnosticsParams {
     /// URI of document for which diagnostic information is reported.
-    uri: String,
+    uri: DocumentUri,
     /// Diagnostic information items.
     diagnostics: Vec<Diagnostic>,
 }
 
 /// A diagnostic such as a compiler error or warning.
 #[lsp_object]
-struct Diagnostic {
+#[derive(Clone)]
+pub struct Diagnostic {
     /// `Range` at which the message applies.
-    range: Range,
+    pub range: Range,
     /// The severity of the diagnostic.
     ///
     /// If `Elective::Absent`, client is responsible for interpreting severity.
-    severity: Elective<DiagnosticSeverity>,
+    pub severity: Elective<DiagnosticSeverity>,
     /// Code of the diagnostic.
-    code: Elective<DiagnosticCode>,
+    pub code: Elective<DiagnosticCode>,
     /// Human-readable description of the source of the diagnostic.
-    source: Elective<String>,
+    pub source: Elective<String>,
     /// Message of the diagnostic.
-    message: String,
+    pub message: String,
     /// Related information about a diagnostic.
-    related_information: Elective<Vec<DiagnosticRelatedInformation>>,
+    pub related_information: Elective<Vec<DiagnosticRelatedInformation>>,
 }
 
 /// Supported severities of a diagnostic.
-#[lsp_kind]
-enum DiagnosticSeverity {
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum DiagnosticSeverity {
     Error = 1,
     Warning,
     Information,
     Hint,
 }
 
+/// The code of a diagnostic.
 #[lsp_kind]
+#[derive(Clone, Eq, PartialEq)]
 #[serde(untagged)]
-enum DiagnosticCode {
+pub enum DiagnosticCode {
     Number(i64),
     String(String),
 }
 
 /// A related message for a `Diagnostic`.
 #[lsp_object]
-struct DiagnosticRelatedInformation {
+#[derive(Clone)]
+pub struct DiagnosticRelatedInformation {
     /// Location of the related information.
-    location: Location,
+    pub location: Location,
     /// Message of the related information.
-    message: String,
+    pub message: String,
 }
 
 /// A `Range` in a text document.
 #[lsp_object]
-struct Location {
-    uri: String,
-    range: Range,
+#[derive(Clone, Eq, PartialEq)]
+pub struct Location {
+    /// URI of the text document.
+    pub uri: DocumentUri,
+    /// `Range` in the text document.
+    pub range: Range,
+}
+
+impl From<LocationLink> for Location {
+    fn from(value: LocationLink) -> Self {
+        Location {
+            uri: value.target_uri,
+            range: value.target_selection_range,
+        }
+    }
+}
+
+/// A link between a source and a target `Location`.
+#[lsp_object]
+#[derive(Clone, Eq, PartialEq)]
+pub struct LocationLink {
+    /// `Range` of the origin of the link.
+    ///
+    /// If `Elective::Absent`, the word range at the requested `Position` is used.
+    pub origin_selection_range: Elective<Range>,
+    /// URI of the target text document.
+    pub target_uri: DocumentUri,
+    /// Full `Range` of the target, such as the body of a function.
+    pub target_range: Range,
+    /// `Range` of the target that should be selected, such as the name of a function.
+    ///
+    /// Must be contained by `target_range`.
+    pub target_selection_range: Range,
+}
+
+impl From<Location> for LocationLink {
+    fn from(value: Location) -> Self {
+        LocationLink {
+            origin_selection_range: Elective::Absent,
+            target_uri: value.uri,
+            target_range: value.range,
+            target_selection_range: value.range,
+        }
+    }
+}
+
+/// Identifies a text document.
+#[lsp_object]
+#[derive(Clone)]
+pub struct TextDocumentIdentifier {
+    /// URI of text document.
+    pub uri: DocumentUri,
+}
+
+/// Identifies a `Position` in a text document.
+#[lsp_object]
+#[derive(Clone)]
+pub struct TextDocumentPositionParams {
+    /// Text document.
+    pub text_document: TextDocumentIdentifier,
+    /// `Position` in the text document.
+    pub position: Position,
+}
+
+/// A textual edit of a text document.
+#[lsp_object]
+#[derive(Clone)]
+pub struct TextEdit {
+    /// `Range` of the text to be replaced.
+    ///
+    /// If `range` is empty, `new_text` is inserted at `range.start`.
+    pub range: Range,
+    /// Text to replace `range`.
+    ///
+    /// If empty, `range` is deleted.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range, new_text: String) -> Self {
+        TextEdit { range, new_text }
+    }
+}
+
+/// Content rendered according to a `MarkupKind`.
+#[lsp_object]
+#[derive(Clone)]
+pub struct MarkupContent {
+    /// The format of `value`.
+    pub kind: MarkupKind,
+    /// The content.
+    pub value: String,
+}
+
+/// The `textDocument/completion` request.
+pub enum Completion {}
+
+impl Request for Completion {
+    type Params = CompletionParams;
+    type Result = Option<CompletionResponse>;
+    const METHOD: &'static str = "textDocument/completion";
+}
+
+/// Request sent from the client to the server to compute completion items at a `Position`.
+#[lsp_object]
+pub struct CompletionParams {
+    /// `Position` at which completion is requested.
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    /// How completion was triggered.
+    ///
+    /// Only present if the client supports context.
+    pub context: Elective<CompletionContext>,
+}
+
+/// Additional information about the context in which completion was triggered.
+#[lsp_object]
+pub struct CompletionContext {
+    /// How completion was triggered.
+    pub trigger_kind: CompletionTriggerKind,
+    /// Character that triggered completion.
+    ///
+    /// Only present if `trigger_kind` is `CompletionTriggerKind::TriggerCharacter`.
+    pub trigger_character: Elective<String>,
+}
+
+/// How completion was triggered.
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum CompletionTriggerKind {
+    /// Completion was triggered by typing an identifier, invoking manually or via API.
+    #[default]
+    Invoked = 1,
+    /// Completion was triggered by a trigger character.
+    TriggerCharacter,
+    /// Completion was re-triggered because the current completion list is incomplete.
+    TriggerForIncompleteCompletions,
+}
+
+/// The result of a `textDocument/completion` request.
+#[lsp_kind]
+#[serde(untagged)]
+pub enum CompletionResponse {
+    /// Complete list of `CompletionItem`s.
+    Array(Vec<CompletionItem>),
+    /// `CompletionList` that may be incomplete.
+    List(CompletionList),
+}
+
+impl CompletionResponse {
+    /// Returns the `CompletionItem`s.
+    pub fn items(&self) -> &[CompletionItem] {
+        match self {
+            CompletionResponse::Array(items) => items,
+            CompletionResponse::List(list) => &list.items,
+        }
+    }
+
+    /// Returns if further typing should recompute the items.
+    pub fn is_incomplete(&self) -> bool {
+        match self {
+            CompletionResponse::Array(_) => false,
+            CompletionResponse::List(list) => list.is_incomplete,
+        }
+    }
+}
+
+/// A collection of `CompletionItem`s.
+#[lsp_object]
+pub struct CompletionList {
+    /// Further typing should recompute the list.
+    pub is_incomplete: bool,
+    /// The items.
+    pub items: Vec<CompletionItem>,
+}
+
+/// The `completionItem/resolve` request.
+pub enum ResolveCompletionItem {}
+
+impl Request for ResolveCompletionItem {
+    type Params = CompletionItem;
+    type Result = CompletionItem;
+    const METHOD: &'static str = "completionItem/resolve";
+}
+
+/// A completion proposal.
+#[lsp_object]
+#[derive(Clone)]
+pub struct CompletionItem {
+    /// Label shown in the completion list.
+    ///
+    /// Also the text inserted when selected if `insert_text` and `text_edit` are absent.
+    pub label: String,
+    /// Kind of the item, used to choose an icon.
+    pub kind: Elective<CompletionItemKind>,
+    /// Additional information such as type or symbol information.
+    pub detail: Elective<String>,
+    /// Doc-comment of the item.
+    pub documentation: Elective<Documentation>,
+    /// Item is deprecated.
+    pub deprecated: Elective<bool>,
+    /// Item is selected when showing the completion list.
+    ///
+    /// Only one item is selected.
+    pub preselect: Elective<bool>,
+    /// String used for comparing items.
+    ///
+    /// If `Elective::Absent`, `label` is used.
+    pub sort_text: Elective<String>,
+    /// String used for filtering items.
+    ///
+    /// If `Elective::Absent`, `label` is used.
+    pub filter_text: Elective<String>,
+    /// Text inserted when the item is selected.
+    ///
+    /// Ignored if `text_edit` is present.
+    pub insert_text: Elective<String>,
+    /// Format of `insert_text` and the `new_text` of `text_edit`.
+    pub insert_text_format: Elective<InsertTextFormat>,
+    /// Edit applied when the item is selected.
+    ///
+    /// `range` must be a single line that contains the `Position` at which completion was
+    /// requested.
+    pub text_edit: Elective<TextEdit>,
+    /// Edits applied when the item is selected that do not overlap with `text_edit`.
+    pub additional_text_edits: Elective<Vec<TextEdit>>,
+    /// Characters that accept the item when typed while the item is selected.
+    pub commit_characters: Elective<Vec<String>>,
+    /// Data preserved between the `textDocument/completion` and `completionItem/resolve` requests.
+    pub data: Elective<Value>,
+}
+
+impl CompletionItem {
+    pub fn new(label: String) -> Self {
+        CompletionItem {
+            label,
+            ..CompletionItem::default()
+        }
+    }
+}
+
+/// Documentation as either a plain string or `MarkupContent`.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(untagged)]
+pub enum Documentation {
+    String(String),
+    MarkupContent(MarkupContent),
+}
+
+/// The format of the insert text of a `CompletionItem`.
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum InsertTextFormat {
+    /// Text is inserted as plain text.
+    PlainText = 1,
+    /// Text is a snippet with tab stops and placeholders.
+    Snippet,
+}
+
+/// The `textDocument/hover` request.
+pub enum HoverRequest {}
+
+impl Request for HoverRequest {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<Hover>;
+    const METHOD: &'static str = "textDocument/hover";
+}
+
+/// The result of a `textDocument/hover` request.
+#[lsp_object]
+#[derive(Clone)]
+pub struct Hover {
+    /// Content of the hover.
+    pub contents: HoverContents,
+    /// `Range` of the text document to which the hover applies.
+    pub range: Elective<Range>,
+}
+
+/// The content of a `Hover`.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(untagged)]
+pub enum HoverContents {
+    Markup(MarkupContent),
+    Scalar(MarkedString),
+    Array(Vec<MarkedString>),
+}
+
+impl Default for HoverContents {
+    fn default() -> Self {
+        HoverContents::Array(Vec::new())
+    }
+}
+
+/// Markdown or a code block.
+///
+/// Deprecated in favor of `MarkupContent`.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(untagged)]
+pub enum MarkedString {
+    /// Markdown.
+    String(String),
+    /// Code block of a language.
+    LanguageString(LanguageString),
+}
+
+/// A code block of a language.
+#[lsp_object]
+#[derive(Clone)]
+pub struct LanguageString {
+    /// Language identifier of the code.
+    pub language: String,
+    /// The code.
+    pub value: String,
+}
+
+/// The `textDocument/signatureHelp` request.
+pub enum SignatureHelpRequest {}
+
+impl Request for SignatureHelpRequest {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<SignatureHelp>;
+    const METHOD: &'static str = "textDocument/signatureHelp";
+}
+
+/// The signature of something callable.
+///
+/// There can be multiple signatures but only one active signature and one active parameter.
+#[lsp_object]
+#[derive(Clone)]
+pub struct SignatureHelp {
+    /// The signatures.
+    pub signatures: Vec<SignatureInformation>,
+    /// Index of the active signature in `signatures`.
+    ///
+    /// If `Elective::Absent`, defaults to 0.
+    pub active_signature: Elective<u64>,
+    /// Index of the active parameter in the active signature.
+    ///
+    /// If `Elective::Absent`, defaults to 0.
+    pub active_parameter: Elective<u64>,
+}
+
+/// The signature of something callable.
+#[lsp_object]
+#[derive(Clone)]
+pub struct SignatureInformation {
+    /// Label shown in the user interface.
+    pub label: String,
+    /// Doc-comment shown in the user interface.
+    pub documentation: Elective<Documentation>,
+    /// The parameters.
+    pub parameters: Elective<Vec<ParameterInformation>>,
+}
+
+/// A parameter of a callable signature.
+#[lsp_object]
+#[derive(Clone)]
+pub struct ParameterInformation {
+    /// Label of the parameter.
+    pub label: ParameterLabel,
+    /// Doc-comment shown in the user interface.
+    pub documentation: Elective<Documentation>,
+}
+
+/// The label of a `ParameterInformation`.
+#[lsp_kind]
+#[derive(Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum ParameterLabel {
+    /// A substring of the label of the `SignatureInformation`.
+    Simple(String),
+    /// Start and exclusive end offsets of the label within the label of the `SignatureInformation`.
+    ///
+    /// Offsets are in UTF-16 code units. Only valid if the client supports label offsets.
+    Offsets([u64; 2]),
+}
+
+impl ParameterLabel {
+    /// Returns the text of the label within `signature_label`.
+    ///
+    /// Returns `Option::None` if the offsets are not within `signature_label`.
+    pub fn text<'a>(&'a self, signature_label: &'a str) -> Option<&'a str> {
+        match self {
+            ParameterLabel::Simple(label) => Some(label),
+            ParameterLabel::Offsets([start, end]) => {
+                let mut units = 0;
+                let mut start_index = None;
+                let mut end_index = None;
+
+                for (index, c) in signature_label.char_indices().chain(std::iter::once((signature_label.len(), '\0'))) {
+                    if units == *start {
+                        start_index = Some(index);
+                    }
+
+                    if units == *end {
+                        end_index = Some(index);
+                        break;
+                    }
+
+                    units += c.len_utf16() as u64;
+                }
+
+                match (start_index, end_index) {
+                    (Some(start_index), Some(end_index)) if start_index <= end_index => {
+                        Some(&signature_label[start_index..end_index])
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl Default for ParameterLabel {
+    fn default() -> Self {
+        ParameterLabel::Simple(String::new())
+    }
+}
+
+/// The `textDocument/declaration` request.
+pub enum GotoDeclaration {}
+
+impl Request for GotoDeclaration {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<GotoResponse>;
+    const METHOD: &'static str = "textDocument/declaration";
+}
+
+/// The `textDocument/definition` request.
+pub enum GotoDefinition {}
+
+impl Request for GotoDefinition {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<GotoResponse>;
+    const METHOD: &'static str = "textDocument/definition";
+}
+
+/// The `textDocument/typeDefinition` request.
+pub enum GotoTypeDefinition {}
+
+impl Request for GotoTypeDefinition {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<GotoResponse>;
+    const METHOD: &'static str = "textDocument/typeDefinition";
+}
+
+/// The `textDocument/implementation` request.
+pub enum GotoImplementation {}
+
+impl Request for GotoImplementation {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<GotoResponse>;
+    const METHOD: &'static str = "textDocument/implementation";
+}
+
+/// The result of a goto request.
+///
+/// `GotoResponse::Link` is only valid if the client supports links for the request.
+#[lsp_kind]
+#[derive(Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum GotoResponse {
+    Scalar(Location),
+    Array(Vec<Location>),
+    Link(Vec<LocationLink>),
+}
+
+impl GotoResponse {
+    /// Converts the response into a list of `Location`s.
+    ///
+    /// A `LocationLink` is converted to the `Location` of its target selection.
+    pub fn into_locations(self) -> Vec<Location> {
+        match self {
+            GotoResponse::Scalar(location) => vec![location],
+            GotoResponse::Array(locations) => locations,
+            GotoResponse::Link(links) => links.into_iter().map(Location::from).collect(),
+        }
+    }
+
+    /// Converts the response into a list of `LocationLink`s.
+    pub fn into_links(self) -> Vec<LocationLink> {
+        match self {
+            GotoResponse::Scalar(location) => vec![location.into()],
+            GotoResponse::Array(locations) => locations.into_iter().map(LocationLink::from).collect(),
+            GotoResponse::Link(links) => links,
+        }
+    }
+}
+
+/// The `textDocument/codeAction` request.
+pub enum CodeActionRequest {}
+
+impl Request for CodeActionRequest {
+    type Params = CodeActionParams;
+    type Result = Option<Vec<CommandOrCodeAction>>;
+    const METHOD: &'static str = "textDocument/codeAction";
+}
+
+/// Request sent from the client to the server to compute commands for a `Range`.
+#[lsp_object]
+pub struct CodeActionParams {
+    /// Text document in which the commands are requested.
+    pub text_document: TextDocumentIdentifier,
+    /// `Range` for which the commands are requested.
+    pub range: Range,
+    /// Context in which the commands are requested.
+    pub context: CodeActionContext,
+}
+
+/// Additional information about the context in which code actions are requested.
+#[lsp_object]
+#[derive(Clone)]
+pub struct CodeActionContext {
+    /// The `Diagnostic`s known by the client that overlap the requested `Range`.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The kinds of the requested code actions.
+    ///
+    /// If present, code actions whose kinds do not match are filtered out by the client.
+    pub only: Elective<Vec<CodeActionKind>>,
+    /// How the code actions were requested.
+    pub trigger_kind: Elective<CodeActionTriggerKind>,
+}
+
+impl CodeActionContext {
+    /// Returns if code actions of `kind` are requested.
+    pub fn requests(&self, kind: &CodeActionKind) -> bool {
+        self.only.as_option().is_none_or(|only| kind.matches(only))
+    }
+}
+
+/// How code actions were requested.
+#[lsp_kind(number)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum CodeActionTriggerKind {
+    /// Code actions were requested explicitly by the user or by an extension.
+    #[default]
+    Invoked = 1,
+    /// Code actions were requested automatically, such as after the selection changed.
+    Automatic,
+}
+
+/// An item of the result of a `textDocument/codeAction` request.
+///
+/// `CommandOrCodeAction::CodeAction` is only valid if the client supports code action literals.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CommandOrCodeAction {
+    Command(Command),
+    CodeAction(Box<CodeAction>),
+}
+
+impl From<Command> for CommandOrCodeAction {
+    fn from(value: Command) -> Self {
+        CommandOrCodeAction::Command(value)
+    }
+}
+
+impl From<CodeAction> for CommandOrCodeAction {
+    fn from(value: CodeAction) -> Self {
+        CommandOrCodeAction::CodeAction(Box::new(value))
+    }
+}
+
+/// A change that can be performed in code, such as fixing a problem or refactoring code.
+///
+/// If both `edit` and `command` are present, `edit` is applied first.
+#[lsp_object]
+#[derive(Clone)]
+pub struct CodeAction {
+    /// Title shown in the user interface.
+    pub title: String,
+    /// Kind of the code action, used to filter code actions.
+    pub kind: Elective<CodeActionKind>,
+    /// The `Diagnostic`s that the code action resolves.
+    pub diagnostics: Elective<Vec<Diagnostic>>,
+    /// The code action is the preferred action of its kind, such as the best fix of an error.
+    pub is_preferred: Elective<bool>,
+    /// Why the code action cannot be applied.
+    pub disabled: Elective<CodeActionDisabled>,
+    /// The edit performed by the code action.
+    pub edit: Elective<WorkspaceEdit>,
+    /// The command executed by the code action.
+    pub command: Elective<Command>,
+    /// Data preserved between the `textDocument/codeAction` and `codeAction/resolve` requests.
+    pub data: Elective<Value>,
+}
+
+/// Why a `CodeAction` is disabled.
+#[lsp_object]
+#[derive(Clone)]
+pub struct CodeActionDisabled {
+    /// Human-readable description of why the code action is disabled.
+    pub reason: String,
+}
+
+/// The `codeAction/resolve` request.
+pub enum ResolveCodeActionRequest {}
+
+impl Request for ResolveCodeActionRequest {
+    type Params = CodeAction;
+    type Result = CodeAction;
+    const METHOD: &'static str = "codeAction/resolve";
+}
+
+/// A reference to a command on the server.
+#[lsp_object]
+#[derive(Clone)]
+pub struct Command {
+    /// Title shown in the user interface.
+    pub title: String,
+    /// Identifier of the command.
+    pub command: String,
+    /// Arguments passed to the command.
+    pub arguments: Elective<Vec<Value>>,
+}
+
+/// The `workspace/executeCommand` request.
+pub enum ExecuteCommand {}
+
+impl Request for ExecuteCommand {
+    type Params = ExecuteCommandParams;
+    type Result = Option<Value>;
+    const METHOD: &'static str = "workspace/executeCommand";
+}
+
+/// Request sent from the client to the server to execute a command.
+#[lsp_object]
+#[derive(Clone)]
+pub struct ExecuteCommandParams {
+    /// Identifier of the command.
+    pub command: String,
+    /// Arguments passed to the command.
+    pub arguments: Elective<Vec<Value>>,
+}
+
+impl From<Command> for ExecuteCommandParams {
+    fn from(value: Command) -> Self {
+        ExecuteCommandParams {
+            command: value.command,
+            arguments: value.arguments,
+        }
+    }
+}
+
+/// The `workspace/applyEdit` request.
+pub enum ApplyWorkspaceEdit {}
+
+impl Request for ApplyWorkspaceEdit {
+    type Params = ApplyWorkspaceEditParams;
+    type Result = ApplyWorkspaceEditResponse;
+    const METHOD: &'static str = "workspace/applyEdit";
+}
+
+/// Request sent from the server to the client to modify resources.
+#[lsp_object]
+pub struct ApplyWorkspaceEditParams {
+    /// Label presented in the user interface, such as on an undo stack.
+    pub label: Elective<String>,
+    /// The edits to apply.
+    pub edit: WorkspaceEdit,
+}
+
+/// The result of a `workspace/applyEdit` request.
+#[lsp_object]
+pub struct ApplyWorkspaceEditResponse {
+    /// The edit was applied.
+    pub applied: bool,
+    /// Textual description of why the edit was not applied.
+    pub failure_reason: Elective<String>,
+    /// Index of the failed change in `WorkspaceEdit::document_changes`.
+    ///
+    /// May be present whenever the edit was not applied, whichever `FailureHandlingKind` the client signals.
+    pub failed_change: Elective<u64>,
+}
+
+/// Changes to many resources managed in the workspace.
+///
+/// Either `changes` or `document_changes` should be present. If the client supports versioned
+/// document changes, `document_changes` is preferred over `changes`.
+#[lsp_object]
+#[derive(Clone)]
+pub struct WorkspaceEdit {
+    /// Changes to existing text documents by URI.
+    pub changes: Elective<HashMap<DocumentUri, Vec<TextEdit>>>,
+    /// Changes to versioned text documents, possibly with resource operations.
+    pub document_changes: Elective<DocumentChanges>,
+}
+
+/// The `document_changes` of a `WorkspaceEdit`.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(untagged)]
+pub enum DocumentChanges {
+    /// Only `TextDocumentEdit`s.
+    Edits(Vec<TextDocumentEdit>),
+    /// `TextDocumentEdit`s mixed with `ResourceOperation`s.
+    ///
+    /// Only valid if the client supports resource operations.
+    Operations(Vec<DocumentChangeOperation>),
+}
+
+/// An entry of `DocumentChanges::Operations`.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(untagged)]
+pub enum DocumentChangeOperation {
+    Op(ResourceOperation),
+    Edit(TextDocumentEdit),
+}
+
+/// An operation on a file or folder.
+#[lsp_kind]
+#[derive(Clone)]
+#[serde(tag = "kind")]
+pub enum ResourceOperation {
+    Create(CreateFile),
+    Rename(RenameFile),
+    Delete(DeleteFile),
+}
+
+impl ResourceOperation {
+    /// Returns the kind of the operation.
+    pub fn kind(&self) -> ResourceOperationKind {
+        match self {
+            ResourceOperation::Create(_) => ResourceOperationKind::Create,
+            ResourceOperation::Rename(_) => ResourceOperationKind::Rename,
+            ResourceOperation::Delete(_) => ResourceOperationKind::Delete,
+        }
+    }
+}
+
+/// Textual changes to a specific version of a text document.
+#[lsp_object]
+#[derive(Clone)]
+pub struct TextDocumentEdit {
+    /// Text document to change.
+    pub text_document: VersionedTextDocumentIdentifier,
+    /// Edits to be applied.
+    pub edits: Vec<TextEdit>,
+}
+
+/// Operation to create a file.
+#[lsp_object]
+#[derive(Clone)]
+pub struct CreateFile {
+    /// URI of the file to create.
+    pub uri: DocumentUri,
+    /// Additional options.
+    pub options: Elective<CreateFileOptions>,
+}
+
+/// Options to create a file.
+#[lsp_object(allow_missing)]
+#[derive(Clone, Copy)]
+pub struct CreateFileOptions {
+    /// Overwrite an existing file.
+    ///
+    /// Overrides `ignore_if_exists`.
+    pub overwrite: bool,
+    /// Ignore the operation if the file already exists.
+    pub ignore_if_exists: bool,
+}
+
+/// Operation to rename a file.
+#[lsp_object]
+#[derive(Clone)]
+pub struct RenameFile {
+    /// URI of the file to rename.
+    pub old_uri: DocumentUri,
+    /// New URI of the file.
+    pub new_uri: DocumentUri,
+    /// Additional options.
+    pub options: Elective<RenameFileOptions>,
+}
+
+/// Options to rename a file.
+#[lsp_object(allow_missing)]
+#[derive(Clone, Copy)]
+pub struct RenameFileOptions {
+    /// Overwrite the target if it exists.
+    ///
+    /// Overrides `ignore_if_exists`.
+    pub overwrite: bool,
+    /// Ignore the operation if the target exists.
+    pub ignore_if_exists: bool,
+}
+
+/// Operation to delete a file.
+#[lsp_object]
+#[derive(Clone)]
+pub struct DeleteFile {
+    /// URI of the file to delete.
+    pub uri: DocumentUri,
+    /// Additional options.
+    pub options: Elective<DeleteFileOptions>,
+}
+
+/// Options to delete a file.
+#[lsp_object(allow_missing)]
+#[derive(Clone, Copy)]
+pub struct DeleteFileOptions {
+    /// Delete the content recursively if a folder is denoted.
+    pub recursive: bool,
+    /// Ignore the operation if the file does not exist.
+    pub ignore_if_not_exists: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::serde_json::{self, json};
+
+    #[test]
+    fn negotiate_position_encoding_skips_other() {
+        let capabilities: ClientCapabilities =
+            serde_json::from_value(json!({"general": {"positionEncodings": ["x-custom", "utf-8", "utf-16"]}})).unwrap();
+        let supported = [PositionEncodingKind::Other("x-custom".to_string()), PositionEncodingKind::Utf8];
+
+        assert_eq!(capabilities.negotiate_position_encoding(&supported), PositionEncodingKind::Utf8);
+        assert_eq!(
+            serde_json::to_value(capabilities.general().position_encodings()).unwrap(),
+            json!(["x-custom", "utf-8", "utf-16"])
+        );
+    }
+
+    #[test]
+    fn byte_offset() {
+        // `é` is 2 UTF-8 code units and `😀` is 4 UTF-8 code units or a UTF-16 surrogate pair.
+        let line = "aé😀b";
+
+        assert_eq!(PositionEncodingKind::Utf8.byte_offset(line, 3), 3);
+        assert_eq!(PositionEncodingKind::Utf8.byte_offset(line, 4), 7);
+        assert_eq!(PositionEncodingKind::Utf16.byte_offset(line, 2), 3);
+        assert_eq!(PositionEncodingKind::Utf16.byte_offset(line, 3), 7);
+        assert_eq!(PositionEncodingKind::Utf16.byte_offset(line, 4), 7);
+        assert_eq!(PositionEncodingKind::Utf16.byte_offset(line, 5), 8);
+        assert_eq!(PositionEncodingKind::Utf32.byte_offset(line, 3), 7);
+        assert_eq!(PositionEncodingKind::Other("x".to_string()).byte_offset(line, 4), 7);
+        assert_eq!(PositionEncodingKind::Utf16.byte_offset(line, 100), line.len());
+    }
+
+    #[test]
+    fn character_offset() {
+        let line = "aé😀b";
+
+        assert_eq!(PositionEncodingKind::Utf8.character_offset(line, 3), 3);
+        assert_eq!(PositionEncodingKind::Utf8.character_offset(line, 5), 3);
+        assert_eq!(PositionEncodingKind::Utf16.character_offset(line, 7), 4);
+        assert_eq!(PositionEncodingKind::Utf16.character_offset(line, 5), 2);
+        assert_eq!(PositionEncodingKind::Utf32.character_offset(line, 7), 3);
+        assert_eq!(PositionEncodingKind::Other("x".to_string()).character_offset(line, 8), 5);
+        assert_eq!(PositionEncodingKind::Utf16.character_offset(line, 100), 5);
+    }
+
+    #[test]
+    fn unknown_kind_of_known_value() {
+        let mut kinds = std::collections::HashSet::new();
+
+        kinds.insert(SymbolKind::Class);
+
+        assert_eq!(SymbolKind::Unknown(SymbolKind::Class.value()), SymbolKind::Class);
+        assert!(kinds.contains(&SymbolKind::Unknown(5)));
+        assert_ne!(SymbolKind::Unknown(1000), SymbolKind::Class);
+    }
+
+    #[test]
+    fn translate_with_encoding() {
+        let edits = [TextEdit::new(Range::new(Position::new(0, 0), Position::new(0, 0)), "é😀".to_string())];
+        let position = Position::new(0, 1);
+
+        assert_eq!(position.translate(&edits), Position::new(0, 4));
+        assert_eq!(
+            position.translate_with(&edits, Stickiness::After, &PositionEncodingKind::Utf8),
+            Position::new(0, 7)
+        );
+        assert_eq!(
+            position.translate_with(&edits, Stickiness::After, &PositionEncodingKind::Utf32),
+            Position::new(0, 3)
+        );
+    }
+
+    #[test]
+    fn language_id_from_file_name() {
+        assert_eq!(LanguageId::from_file_name("Makefile"), Some(LanguageId::Makefile));
+        assert_eq!(LanguageId::from_file_name("Cargo.lock"), Some(LanguageId::Other("toml".to_string())));
+        assert_eq!(LanguageId::from_file_name("Cargo.toml"), None);
+        assert_eq!(LanguageId::from_file_name("main.rs"), None);
+    }
+
+    #[test]
+    fn language_id_from_extension() {
+        assert_eq!(LanguageId::from_extension("rs"), Some(LanguageId::Rust));
+        assert_eq!(LanguageId::from_extension("RS"), Some(LanguageId::Rust));
+        assert_eq!(LanguageId::from_extension("toml"), Some(LanguageId::Other("toml".to_string())));
+        assert_eq!(LanguageId::from_extension("mk"), Some(LanguageId::Makefile));
+        assert_eq!(LanguageId::from_extension("unknown"), None);
+    }
+
+    #[test]
+    fn language_id_from_path() {
+        assert_eq!(LanguageId::from_path("src/main.rs"), Some(LanguageId::Rust));
+        assert_eq!(LanguageId::from_path("crate/Cargo.toml"), Some(LanguageId::Other("toml".to_string())));
+        assert_eq!(LanguageId::from_path("crate/Makefile"), Some(LanguageId::Makefile));
+        assert_eq!(LanguageId::from_path("crate/makefile.rs"), Some(LanguageId::Rust));
+        assert_eq!(LanguageId::from_path("LICENSE"), None);
+    }
+
+    #[test]
+    fn language_id_serde() {
+        assert_eq!(serde_json::to_value(LanguageId::default()).unwrap(), json!("plaintext"));
+        assert_eq!(LanguageId::default().to_string(), "plaintext");
+        assert_eq!(serde_json::from_value::<LanguageId>(json!("rust")).unwrap(), LanguageId::Rust);
+        assert_eq!(serde_json::from_value::<LanguageId>(json!("toml")).unwrap(), LanguageId::Other("toml".to_string()));
+    }
+
+    #[test]
+    fn code_action_kind_contains() {
+        let refactorx = CodeActionKind::new("refactorx");
+        let extract = CodeActionKind::new("refactor.extract");
+
+        assert!(CodeActionKind::REFACTOR.contains(&CodeActionKind::REFACTOR));
+        assert!(CodeActionKind::REFACTOR.contains(&extract));
+        assert!(!CodeActionKind::REFACTOR.contains(&refactorx));
+        assert!(!extract.contains(&CodeActionKind::REFACTOR));
+        assert!(CodeActionKind::EMPTY.contains(&refactorx));
+        assert!(CodeActionKind::EMPTY.contains(&CodeActionKind::EMPTY));
+        assert!(!CodeActionKind::REFACTOR.contains(&CodeActionKind::EMPTY));
+    }
+
+    #[test]
+    fn code_action_kind_is_subkind_of() {
+        let refactorx = CodeActionKind::new("refactorx");
+
+        assert!(CodeActionKind::REFACTOR_EXTRACT.is_subkind_of(&CodeActionKind::REFACTOR));
+        assert!(!refactorx.is_subkind_of(&CodeActionKind::REFACTOR));
+        assert!(refactorx.is_subkind_of(&CodeActionKind::EMPTY));
+        assert!(!CodeActionKind::EMPTY.is_subkind_of(&CodeActionKind::REFACTOR));
+    }
+
+    #[test]
+    fn code_action_kind_matches() {
+        let refactorx = CodeActionKind::new("refactorx");
+        let filter = [CodeActionKind::QUICK_FIX, CodeActionKind::REFACTOR];
+
+        assert!(refactorx.matches(&[]));
+        assert!(CodeActionKind::EMPTY.matches(&[]));
+        assert!(CodeActionKind::REFACTOR_INLINE.matches(&filter));
+        assert!(!refactorx.matches(&filter));
+        assert!(!CodeActionKind::EMPTY.matches(&filter));
+        assert!(CodeActionKind::SOURCE.matches(&[CodeActionKind::EMPTY]));
+    }
+
+    fn code_actions() -> Vec<CommandOrCodeAction> {
+        let command = json!({"title": "Run", "command": "run"});
+
+        serde_json::from_value(json!([
+            command,
+            {"title": "Fix", "kind": "quickfix", "isPreferred": true, "command": command},
+            {"title": "Edit", "edit": {"changes": {}}, "command": command},
+            {"title": "Disabled", "disabled": {"reason": "no"}, "command": command},
+            {"title": "Empty"},
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn supported_actions_without_literals() {
+        let capabilities: CodeActionCapabilities = serde_json::from_value(json!({})).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(capabilities.supported_actions(code_actions())).unwrap(),
+            json!([
+                {"title": "Run", "command": "run"},
+                {"title": "Run", "command": "run"},
+                {"title": "Run", "command": "run"},
+            ])
+        );
+    }
+
+    #[test]
+    fn supported_actions_with_literals() {
+        let literal_support = json!({"codeActionKind": {"valueSet": ["quickfix"]}});
+        let capabilities: CodeActionCapabilities =
+            serde_json::from_value(json!({"codeActionLiteralSupport": literal_support})).unwrap();
+        let titles = |actions: Vec<CommandOrCodeAction>| -> Vec<serde_json::Value> {
+            actions.into_iter().map(|action| serde_json::to_value(action).unwrap()["title"].clone()).collect()
+        };
+
+        assert_eq!(titles(capabilities.supported_actions(code_actions())), ["Run", "Fix", "Edit", "Empty"]);
+
+        match &capabilities.supported_actions(code_actions())[1] {
+            CommandOrCodeAction::CodeAction(action) => assert!(action.is_preferred.is_absent()),
+            CommandOrCodeAction::Command(_) => panic!("expected a code action"),
+        }
+
+        let capabilities = capabilities.with_disabled_support(true).with_is_preferred_support(true);
+
+        assert_eq!(
+            titles(capabilities.supported_actions(code_actions())),
+            ["Run", "Fix", "Edit", "Disabled", "Empty"]
+        );
+
+        match &capabilities.supported_actions(code_actions())[1] {
+            CommandOrCodeAction::CodeAction(action) => assert_eq!(action.is_preferred, Elective::Present(true)),
+            CommandOrCodeAction::Command(_) => panic!("expected a code action"),
+        }
+    }
+
+    #[test]
+    fn position_checked_moves() {
+        let origin = Position::new(0, 0);
+        let last = Position::new(u64::MAX, u64::MAX);
+
+        assert_eq!(origin.checked_up(), None);
+        assert_eq!(origin.checked_left(), None);
+        assert_eq!(origin.checked_down(), Some(Position::new(1, 0)));
+        assert_eq!(origin.checked_right(), Some(Position::new(0, 1)));
+        assert_eq!(last.checked_down(), None);
+        assert_eq!(last.checked_right(), None);
+        assert_eq!(last.checked_up(), Some(Position::new(u64::MAX - 1, u64::MAX)));
+        assert_eq!(last.checked_left(), Some(Position::new(u64::MAX, u64::MAX - 1)));
+    }
+
+    #[test]
+    fn position_saturating_moves() {
+        let mut position = Position::new(0, 0);
+
+        position.move_up();
+        position.move_left();
+        assert_eq!(position, Position::new(0, 0));
+
+        position = Position::new(u64::MAX, u64::MAX);
+        position.move_down();
+        position.move_right();
+        assert_eq!(position, Position::new(u64::MAX, u64::MAX));
+
+        position.move_up();
+        position.move_left();
+        assert_eq!(position, Position::new(u64::MAX - 1, u64::MAX - 1));
+    }
+
+    #[test]
+    fn range_contains() {
+        let empty = Range::from(Position::new(1, 2));
+        let range = Range::with_partial_line(1, 2, 4);
+        let reversed = Range::new(range.end, range.start);
+
+        assert!(!empty.contains(Position::new(1, 2)));
+        assert!(range.contains(Position::new(1, 2)));
+        assert!(!range.contains(Position::new(1, 4)));
+        assert!(reversed.contains(Position::new(1, 2)));
+        assert!(!reversed.contains(Position::new(1, 4)));
+        assert!(range.contains_range(&empty));
+        assert!(range.contains_range(&reversed));
+        assert!(!empty.contains_range(&range));
+    }
+
+    #[test]
+    fn range_intersects() {
+        let range = Range::with_partial_line(1, 2, 4);
+        let touching = Range::with_partial_line(1, 4, 6);
+        let reversed = Range::new(Position::new(1, 5), Position::new(1, 3));
+        let empty = Range::from(Position::new(1, 3));
+
+        assert!(!range.intersects(&touching));
+        assert!(!touching.intersects(&range));
+        assert!(range.intersects(&reversed));
+        assert!(reversed.intersects(&range));
+        assert!(!range.intersects(&empty));
+        assert!(!empty.intersects(&range));
+        assert!(!empty.intersects(&empty));
+    }
+
+    #[test]
+    fn range_union() {
+        let range = Range::with_partial_line(1, 2, 4);
+        let touching = Range::with_partial_line(1, 4, 6);
+        let reversed = Range::new(Position::new(2, 0), Position::new(0, 5));
+        let empty = Range::from(Position::new(3, 0));
+
+        assert_eq!(range.union(&touching), Range::with_partial_line(1, 2, 6));
+        assert_eq!(range.union(&reversed), Range::new(Position::new(0, 5), Position::new(2, 0)));
+        assert_eq!(range.union(&empty), Range::new(Position::new(1, 2), Position::new(3, 0)));
+        assert_eq!(empty.union(&empty), empty);
+    }
+
+    #[test]
+    fn range_normalized() {
+        let range = Range::with_partial_line(1, 2, 4);
+        let reversed = Range::new(range.end, range.start);
+        let empty = Range::from(Position::new(1, 2));
+
+        assert!(reversed.is_reversed());
+        assert!(!range.is_reversed());
+        assert!(!empty.is_reversed());
+        assert!(empty.is_empty());
+        assert_eq!(reversed.normalized(), range);
+        assert_eq!(range.normalized(), range);
+        assert_eq!(empty.normalized(), empty);
+    }
 }
@@ -0,0 +1,549 @@
+use crate::codec::{CodecError, Decoder, Encoder, Message};
+use crate::{
+    ExecuteCommand, ExecuteCommandOptions, ExecuteCommandParams, Exit, Initialize, InitializeParams, InitializeResult,
+    Notification, Request, ServerCapabilities, Shutdown,
+};
+use jsonrpc_core::{serde_json, ErrorCode, Id, MethodCall, Params, Value};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, Write};
+
+/// The error code of a request received before the server is initialized.
+pub const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+/// Observes the params of the `initialize` request.
+type InitializeHandler = Box<dyn FnMut(&InitializeParams)>;
+/// Handles the params of a request.
+type RequestHandler = Box<dyn FnMut(Params, &mut Outbox) -> jsonrpc_core::Result<Value>>;
+/// Handles the params of a notification.
+type NotificationHandler = Box<dyn FnMut(Params, &mut Outbox)>;
+/// Handles the arguments of a command.
+type CommandHandler = Box<dyn FnMut(Vec<Value>, &mut Outbox) -> jsonrpc_core::Result<Value>>;
+
+/// An error that stops a `Server` from running.
+#[derive(Debug)]
+pub enum ServerError {
+    /// A message could not be written or read.
+    Codec(CodecError),
+    /// The client closed its output without sending the `exit` notification.
+    Disconnected,
+    /// The client sent the `exit` notification without sending the `shutdown` request.
+    ExitWithoutShutdown,
+}
+
+impl Display for ServerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Codec(error) => write!(f, "codec error: {}", error),
+            ServerError::Disconnected => write!(f, "client disconnected"),
+            ServerError::ExitWithoutShutdown => write!(f, "client sent exit before shutdown"),
+        }
+    }
+}
+
+impl Error for ServerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ServerError::Codec(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CodecError> for ServerError {
+    fn from(value: CodecError) -> Self {
+        ServerError::Codec(value)
+    }
+}
+
+/// The lifecycle state of a `Server`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    /// The `initialize` request has not been received.
+    Uninitialized,
+    /// The `initialize` request has been received.
+    Initialized,
+    /// The `shutdown` request has been received.
+    ShutDown,
+}
+
+/// Messages that handlers send to the client.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    /// The messages to be sent.
+    messages: Vec<Message>,
+}
+
+impl Outbox {
+    /// Queues a notification of type `T` to be sent to the client.
+    pub fn notify<T: Notification>(&mut self, params: &T::Params) -> Result<(), serde_json::Error> {
+        self.messages.push(Message::notification::<T>(params)?);
+        Ok(())
+    }
+}
+
+/// A language server that dispatches messages to typed handlers.
+pub struct Server {
+    /// The capabilities sent in response to the `initialize` request.
+    capabilities: Option<ServerCapabilities>,
+    /// Called with the params of the `initialize` request.
+    initialize_handler: Option<InitializeHandler>,
+    /// Handlers of requests by method.
+    request_handlers: HashMap<&'static str, RequestHandler>,
+    /// Handlers of notifications by method.
+    notification_handlers: HashMap<&'static str, NotificationHandler>,
+    /// The commands advertised in `capabilities` along with the commands that have handlers.
+    commands: Vec<String>,
+    /// Handlers of commands by identifier.
+    command_handlers: HashMap<String, CommandHandler>,
+    /// The lifecycle state.
+    state: State,
+}
+
+impl Server {
+    /// Creates a `Server` that advertises `capabilities`.
+    pub fn new(capabilities: ServerCapabilities) -> Self {
+        Server {
+            commands: capabilities.commands().to_vec(),
+            capabilities: Some(capabilities),
+            initialize_handler: None,
+            request_handlers: HashMap::new(),
+            notification_handlers: HashMap::new(),
+            command_handlers: HashMap::new(),
+            state: State::Uninitialized,
+        }
+    }
+
+    /// Sets the handler called with the params of the `initialize` request.
+    pub fn on_initialize<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&InitializeParams) + 'static,
+    {
+        self.initialize_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets the handler of requests of type `T`.
+    ///
+    /// A handler of `ExecuteCommand` requests is only called with commands advertised in the
+    /// capabilities of the server.
+    ///
+    /// # Panics
+    ///
+    /// If `T` is `ExecuteCommand` and a handler of a command has been set by `Server::on_command`.
+    pub fn on_request<T, F>(mut self, mut handler: F) -> Self
+    where
+        T: Request,
+        F: FnMut(T::Params, &mut Outbox) -> jsonrpc_core::Result<T::Result> + 'static,
+    {
+        assert!(
+            T::METHOD != ExecuteCommand::METHOD || self.command_handlers.is_empty(),
+            "a handler of `ExecuteCommand` requests cannot be combined with handlers of commands"
+        );
+        self.request_handlers.insert(
+            T::METHOD,
+            Box::new(move |params, outbox| {
+                let result = handler(params.parse()?, outbox)?;
+
+                serde_json::to_value(result).map_err(|_| jsonrpc_core::Error::internal_error())
+            }),
+        );
+        self
+    }
+
+    /// Sets the handler of notifications of type `T`.
+    ///
+    /// Notifications with invalid params are dropped.
+    pub fn on_notification<T, F>(mut self, mut handler: F) -> Self
+    where
+        T: Notification,
+        F: FnMut(T::Params, &mut Outbox) + 'static,
+    {
+        self.notification_handlers.insert(
+            T::METHOD,
+            Box::new(move |params, outbox| {
+                if let Ok(params) = params.parse() {
+                    handler(params, outbox);
+                }
+            }),
+        );
+        self
+    }
+
+    /// Sets the handler of the command identified by `command`.
+    ///
+    /// `command` is added to the commands advertised in the capabilities of the server and a
+    /// `workspace/executeCommand` request is dispatched to the handler of its command. The
+    /// arguments of the command are decoded as a sequence, such as a tuple; a command without
+    /// arguments may also be decoded as `()`.
+    ///
+    /// # Panics
+    ///
+    /// If a handler of `ExecuteCommand` requests has been set by `Server::on_request`.
+    pub fn on_command<A, R, F>(mut self, command: &str, mut handler: F) -> Self
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        F: FnMut(A, &mut Outbox) -> jsonrpc_core::Result<R> + 'static,
+    {
+        assert!(
+            !self.request_handlers.contains_key(ExecuteCommand::METHOD),
+            "handlers of commands cannot be combined with a handler of `ExecuteCommand` requests"
+        );
+
+        if !self.commands.iter().any(|advertised| advertised == command) {
+            self.commands.push(command.to_string());
+        }
+
+        self.command_handlers.insert(
+            command.to_string(),
+            Box::new(move |arguments, outbox| {
+                let is_empty = arguments.is_empty();
+                let arguments = serde_json::from_value(Value::Array(arguments))
+                    .or_else(|error| {
+                        if is_empty {
+                            serde_json::from_value(Value::Null)
+                        } else {
+                            Err(error)
+                        }
+                    })
+                    .map_err(|error| jsonrpc_core::Error::invalid_params(format!("invalid arguments: {}", error)))?;
+                let result = handler(arguments, outbox)?;
+
+                serde_json::to_value(result).map_err(|_| jsonrpc_core::Error::internal_error())
+            }),
+        );
+        self
+    }
+
+    /// Processes messages from `reader` and writes messages to `writer` until the `exit`
+    /// notification is received.
+    ///
+    /// A message that is not valid JSON-RPC is answered with a `ParseError` or `InvalidRequest`
+    /// error; only a failure of the stream or the framing of messages stops the `Server`.
+    pub fn run<R: BufRead, W: Write>(mut self, reader: R, writer: W) -> Result<(), ServerError> {
+        let mut decoder = Decoder::new(reader);
+        let mut encoder = Encoder::new(writer);
+        let mut outbox = Outbox::default();
+
+        loop {
+            let content = match decoder.read_content()? {
+                Some(content) => content,
+                None => return Err(ServerError::Disconnected),
+            };
+            let message = match serde_json::from_str(&content) {
+                Ok(message) => message,
+                Err(error) => {
+                    // The framing of the next message is intact, so only the malformed message
+                    // is rejected.
+                    encoder.write(&Message::response(request_id(&content), Err(rejection(&error))))?;
+                    continue;
+                }
+            };
+
+            match message {
+                Message::Request(method_call) => {
+                    let id = method_call.id.clone();
+                    let result = self.handle_request(method_call, &mut outbox);
+
+                    for message in outbox.messages.drain(..) {
+                        encoder.write(&message)?;
+                    }
+
+                    encoder.write(&Message::response(id, result))?;
+                }
+                Message::Notification(notification) => {
+                    if notification.method == Exit::METHOD {
+                        return match self.state {
+                            State::ShutDown => Ok(()),
+                            _ => Err(ServerError::ExitWithoutShutdown),
+                        };
+                    }
+
+                    // Notifications received before initialization or after shutdown are dropped.
+                    if self.state == State::Initialized {
+                        if let Some(handler) = self.notification_handlers.get_mut(notification.method.as_str()) {
+                            handler(notification.params, &mut outbox);
+                        }
+                    }
+
+                    for message in outbox.messages.drain(..) {
+                        encoder.write(&message)?;
+                    }
+                }
+                // The server does not send requests, so responses are ignored.
+                Message::Response(_) => {}
+            }
+        }
+    }
+
+    /// Returns the result of `method_call`.
+    fn handle_request(&mut self, method_call: MethodCall, outbox: &mut Outbox) -> jsonrpc_core::Result<Value> {
+        let method = method_call.method.as_str();
+
+        match self.state {
+            State::Uninitialized => {
+                if method != Initialize::METHOD {
+                    return Err(jsonrpc_core::Error {
+                        code: ErrorCode::ServerError(SERVER_NOT_INITIALIZED),
+                        message: String::from("Server not initialized"),
+                        data: None,
+                    });
+                }
+
+                let params: InitializeParams = method_call.params.parse()?;
+
+                if let Some(handler) = self.initialize_handler.as_mut() {
+                    handler(&params);
+                }
+
+                let mut capabilities = self.capabilities.take().unwrap_or_default();
+
+                // Commands with handlers are advertised so that the client can execute them.
+                if capabilities.commands() != self.commands.as_slice() {
+                    let options = ExecuteCommandOptions::default().with_commands(self.commands.clone());
+
+                    capabilities = capabilities.with_execute_command_provider(options);
+                }
+
+                self.state = State::Initialized;
+                serde_json::to_value(InitializeResult { capabilities })
+                    .map_err(|_| jsonrpc_core::Error::internal_error())
+            }
+            State::Initialized => {
+                if method == Initialize::METHOD {
+                    return Err(jsonrpc_core::Error::invalid_request());
+                }
+
+                if method == Shutdown::METHOD {
+                    self.state = State::ShutDown;
+                    return Ok(Value::Null);
+                }
+
+                if method == ExecuteCommand::METHOD {
+                    return self.execute_command(method_call.params, outbox);
+                }
+
+                match self.request_handlers.get_mut(method) {
+                    Some(handler) => handler(method_call.params, outbox),
+                    None => Err(jsonrpc_core::Error::method_not_found()),
+                }
+            }
+            State::ShutDown => Err(jsonrpc_core::Error::invalid_request()),
+        }
+    }
+
+    /// Returns the result of executing the command of `params`.
+    ///
+    /// Only commands that are advertised in the capabilities of the server are executed.
+    fn execute_command(&mut self, params: Params, outbox: &mut Outbox) -> jsonrpc_core::Result<Value> {
+        let request_handler = self.request_handlers.get_mut(ExecuteCommand::METHOD);
+
+        if request_handler.is_none() && self.command_handlers.is_empty() {
+            return Err(jsonrpc_core::Error::method_not_found());
+        }
+
+        let command_params: ExecuteCommandParams = params.clone().parse()?;
+
+        if !self.commands.contains(&command_params.command) {
+            return Err(jsonrpc_core::Error::invalid_params(format!(
+                "command `{}` is not advertised",
+                command_params.command
+            )));
+        }
+
+        if let Some(handler) = request_handler {
+            return handler(params, outbox);
+        }
+
+        match self.command_handlers.get_mut(&command_params.command) {
+            Some(handler) => handler(
+                command_params.arguments.as_option().cloned().unwrap_or_default(),
+                outbox,
+            ),
+            None => Err(jsonrpc_core::Error::invalid_params(format!(
+                "command `{}` has no handler",
+                command_params.command
+            ))),
+        }
+    }
+}
+
+/// Returns the error of a message that could not be deserialized due to `error`.
+fn rejection(error: &serde_json::Error) -> jsonrpc_core::Error {
+    if error.is_syntax() || error.is_eof() {
+        jsonrpc_core::Error::parse_error()
+    } else {
+        jsonrpc_core::Error::invalid_request()
+    }
+}
+
+/// Returns the id of the request in `content`, or `Id::Null` if it cannot be determined.
+fn request_id(content: &str) -> Id {
+    serde_json::from_str::<Value>(content)
+        .ok()
+        .and_then(|message| serde_json::from_value(message.get("id")?.clone()).ok())
+        .unwrap_or(Id::Null)
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("capabilities", &self.capabilities)
+            .field("requests", &self.request_handlers.keys().collect::<Vec<_>>())
+            .field("notifications", &self.notification_handlers.keys().collect::<Vec<_>>())
+            .field("commands", &self.command_handlers.keys().collect::<Vec<_>>())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DidOpenTextDocument, DidOpenTextDocumentParams};
+    use jsonrpc_core::serde_json::json;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Returns `messages` framed by `Content-Length` headers.
+    fn frame(messages: &[Value]) -> Vec<u8> {
+        let mut encoder = Encoder::new(Vec::new());
+
+        for message in messages {
+            encoder.write(message).unwrap();
+        }
+
+        encoder.into_inner()
+    }
+
+    /// Runs `server` with `requests` between the initialize and shutdown requests, returning the
+    /// responses to `requests`.
+    fn run(server: Server, requests: &[Value]) -> Vec<Value> {
+        let mut messages = vec![json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {"processId": null, "rootUri": null, "capabilities": {}},
+        })];
+        let mut output = Vec::new();
+
+        messages.extend_from_slice(requests);
+        messages.push(json!({"jsonrpc": "2.0", "id": null, "method": "shutdown"}));
+        messages.push(json!({"jsonrpc": "2.0", "method": "exit"}));
+        server.run(&frame(&messages)[..], &mut output).unwrap();
+
+        let mut decoder = Decoder::new(&output[..]);
+        let mut responses = Vec::new();
+
+        while let Some(response) = decoder.read::<Value>().unwrap() {
+            responses.push(response);
+        }
+
+        responses[..responses.len() - 1].to_vec()
+    }
+
+    /// Returns a `workspace/executeCommand` request of `command`.
+    fn execute_command(id: u64, command: &str) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": "workspace/executeCommand", "params": {"command": command}})
+    }
+
+    #[test]
+    fn notifications_after_shutdown_are_dropped() {
+        let opened = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&opened);
+        let did_open = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": "file:///a", "languageId": "rust", "version": 0, "text": ""}},
+        });
+        let input = frame(&[
+            did_open.clone(),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "initialize",
+                "params": {"processId": null, "rootUri": null, "capabilities": {}},
+            }),
+            did_open.clone(),
+            json!({"jsonrpc": "2.0", "id": 1, "method": "shutdown"}),
+            did_open,
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ]);
+        let server = Server::new(ServerCapabilities::default()).on_notification::<DidOpenTextDocument, _>(
+            move |_: DidOpenTextDocumentParams, _| counter.set(counter.get() + 1),
+        );
+
+        server.run(&input[..], Vec::new()).unwrap();
+        assert_eq!(opened.get(), 1);
+    }
+
+    #[test]
+    fn commands_with_handlers_are_advertised() {
+        let server = Server::new(ServerCapabilities::default()).on_command("a", |(): (), _| Ok(1));
+        let responses = run(server, &[execute_command(1, "a"), execute_command(2, "b")]);
+
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["executeCommandProvider"]["commands"],
+            json!(["a"])
+        );
+        assert_eq!(responses[1]["result"], json!(1));
+        assert_eq!(responses[2]["error"]["code"], json!(-32602));
+    }
+
+    #[test]
+    fn execute_command_requests_are_advertised() {
+        let options = ExecuteCommandOptions::default().with_commands(vec!["a".to_string()]);
+        let server = Server::new(ServerCapabilities::default().with_execute_command_provider(options))
+            .on_request::<ExecuteCommand, _>(|params, _| Ok(Some(json!(params.command))));
+        let responses = run(server, &[execute_command(1, "a"), execute_command(2, "b")]);
+
+        assert_eq!(responses[1]["result"], json!("a"));
+        assert_eq!(responses[2]["error"]["code"], json!(-32602));
+    }
+
+    #[test]
+    #[should_panic]
+    fn execute_command_requests_with_commands() {
+        let _ = Server::new(ServerCapabilities::default())
+            .on_command("a", |(): (), _| Ok(()))
+            .on_request::<ExecuteCommand, _>(|_, _| Ok(None));
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"processId": null, "rootUri": null, "capabilities": {}},
+        });
+        let mut extra_field = initialize.clone();
+        let mut input = b"Content-Length: 1\r\n\r\n{".to_vec();
+        let mut output = Vec::new();
+
+        extra_field["extra"] = json!(true);
+        input.extend(frame(&[
+            extra_field,
+            initialize,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ]));
+        Server::new(ServerCapabilities::default())
+            .run(&input[..], &mut output)
+            .unwrap();
+
+        let mut decoder = Decoder::new(&output[..]);
+        let parse_error = decoder.read::<Value>().unwrap().unwrap();
+        let invalid_request = decoder.read::<Value>().unwrap().unwrap();
+
+        assert_eq!(parse_error["error"]["code"], json!(-32700));
+        assert_eq!(parse_error["id"], Value::Null);
+        assert_eq!(invalid_request["error"]["code"], json!(-32600));
+        assert_eq!(invalid_request["id"], json!(1));
+        assert!(decoder.read::<Value>().unwrap().unwrap()["result"]["capabilities"].is_object());
+    }
+}
@@ -0,0 +1,317 @@
+use crate::client::{Client, ClientError};
+use crate::diff::diff;
+use crate::uri::DocumentUri;
+use crate::{
+    DidChangeTextDocument, DidChangeTextDocumentParams, DidCloseTextDocument, DidCloseTextDocumentParams,
+    DidOpenTextDocument, DidOpenTextDocumentParams, DidSaveTextDocument, DidSaveTextDocumentParams, Elective,
+    ServerCapabilities, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentSaveReason, TextDocumentSyncKind, TextDocumentSyncProvider, VersionedTextDocumentIdentifier,
+    WillSaveTextDocument, WillSaveTextDocumentParams,
+};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, Write};
+
+/// An error that prevents a `Synchronizer` from tracking a document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SyncError {
+    /// The document at the URI is already open.
+    AlreadyOpen(DocumentUri),
+    /// The document at the URI is not open.
+    NotOpen(DocumentUri),
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::AlreadyOpen(uri) => write!(f, "`{}` is already open", uri),
+            SyncError::NotOpen(uri) => write!(f, "`{}` is not open", uri),
+        }
+    }
+}
+
+impl Error for SyncError {}
+
+/// A notification that keeps the server in sync with the documents of the client.
+#[derive(Debug)]
+pub enum SyncNotification {
+    /// The `textDocument/didOpen` notification.
+    DidOpen(DidOpenTextDocumentParams),
+    /// The `textDocument/didChange` notification.
+    DidChange(DidChangeTextDocumentParams),
+    /// The `textDocument/willSave` notification.
+    WillSave(WillSaveTextDocumentParams),
+    /// The `textDocument/didSave` notification.
+    DidSave(DidSaveTextDocumentParams),
+    /// The `textDocument/didClose` notification.
+    DidClose(DidCloseTextDocumentParams),
+}
+
+impl SyncNotification {
+    /// Sends the notification with `client`.
+    pub fn send<R: BufRead, W: Write>(&self, client: &mut Client<R, W>) -> Result<(), ClientError> {
+        match self {
+            SyncNotification::DidOpen(params) => client.notify::<DidOpenTextDocument>(params),
+            SyncNotification::DidChange(params) => client.notify::<DidChangeTextDocument>(params),
+            SyncNotification::WillSave(params) => client.notify::<WillSaveTextDocument>(params),
+            SyncNotification::DidSave(params) => client.notify::<DidSaveTextDocument>(params),
+            SyncNotification::DidClose(params) => client.notify::<DidCloseTextDocument>(params),
+        }
+    }
+}
+
+/// Tracks the open documents of a client and decides which notifications keep the server in
+/// sync with them.
+///
+/// Each method returns `Option::None` if the server did not ask for the message.
+#[derive(Clone, Debug)]
+pub struct Synchronizer {
+    /// Sends open and close notifications.
+    open_close: bool,
+    /// How changes are sent.
+    change: TextDocumentSyncKind,
+    /// Sends will save notifications.
+    will_save: bool,
+    /// Sends will save wait until requests.
+    will_save_wait_until: bool,
+    /// Sends save notifications.
+    save: bool,
+    /// Includes the content of the document in save notifications.
+    include_text: bool,
+    /// Open documents by URI.
+    documents: HashMap<DocumentUri, TextDocumentItem>,
+}
+
+impl Synchronizer {
+    pub fn new(provider: &TextDocumentSyncProvider) -> Self {
+        Synchronizer {
+            open_close: provider.open_close(),
+            change: provider.change(),
+            will_save: provider.will_save(),
+            will_save_wait_until: provider.will_save_wait_until(),
+            save: provider.save(),
+            include_text: provider.include_text(),
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Creates a `Synchronizer` for a server with `capabilities`.
+    pub fn from_capabilities(capabilities: &ServerCapabilities) -> Self {
+        Synchronizer::new(capabilities.text_document_sync())
+    }
+
+    /// Returns the open document at `uri`.
+    pub fn get(&self, uri: &DocumentUri) -> Option<&TextDocumentItem> {
+        self.documents.get(uri)
+    }
+
+    /// Returns an iterator over the open documents.
+    pub fn documents(&self) -> impl Iterator<Item = &TextDocumentItem> {
+        self.documents.values()
+    }
+
+    /// Opens `document`.
+    pub fn open(&mut self, document: TextDocumentItem) -> Result<Option<SyncNotification>, SyncError> {
+        if self.documents.contains_key(&document.uri) {
+            return Err(SyncError::AlreadyOpen(document.uri));
+        }
+
+        let notification = if self.open_close {
+            Some(SyncNotification::DidOpen(document.clone().into()))
+        } else {
+            None
+        };
+
+        self.documents.insert(document.uri.clone(), document);
+        Ok(notification)
+    }
+
+    /// Replaces the content of the document at `uri` with `text` and increments its version.
+    ///
+    /// With `TextDocumentSyncKind::Incremental`, only the changed text is sent. If `text` equals
+    /// the content of the document, the document is left unchanged and nothing is sent.
+    pub fn change(&mut self, uri: &DocumentUri, text: String) -> Result<Option<SyncNotification>, SyncError> {
+        let document = self.documents.get_mut(uri).ok_or_else(|| SyncError::NotOpen(uri.clone()))?;
+
+        if document.text == text {
+            return Ok(None);
+        }
+
+        let content_changes = match self.change {
+            TextDocumentSyncKind::None => Vec::new(),
+            TextDocumentSyncKind::Full => vec![TextDocumentContentChangeEvent::full(text.clone())],
+            TextDocumentSyncKind::Incremental => diff(&document.text, &text),
+        };
+
+        document.text = text;
+        document.increment_version();
+
+        if self.change == TextDocumentSyncKind::None {
+            return Ok(None);
+        }
+
+        Ok(Some(SyncNotification::DidChange(DidChangeTextDocumentParams::new(
+            VersionedTextDocumentIdentifier {
+                uri: document.uri.clone(),
+                version: Some(document.version),
+            },
+            content_changes,
+        ))))
+    }
+
+    /// Signals that the document at `uri` will be saved for `reason`.
+    pub fn will_save(
+        &self,
+        uri: &DocumentUri,
+        reason: TextDocumentSaveReason,
+    ) -> Result<Option<SyncNotification>, SyncError> {
+        let document = self.documents.get(uri).ok_or_else(|| SyncError::NotOpen(uri.clone()))?;
+
+        if !self.will_save {
+            return Ok(None);
+        }
+
+        Ok(Some(SyncNotification::WillSave(WillSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: document.uri.clone() },
+            reason,
+        })))
+    }
+
+    /// Returns the params of the `textDocument/willSaveWaitUntil` request for the document at
+    /// `uri` before it is saved for `reason`.
+    ///
+    /// Edits in the response should be applied before the document is saved.
+    pub fn will_save_wait_until(
+        &self,
+        uri: &DocumentUri,
+        reason: TextDocumentSaveReason,
+    ) -> Result<Option<WillSaveTextDocumentParams>, SyncError> {
+        let document = self.documents.get(uri).ok_or_else(|| SyncError::NotOpen(uri.clone()))?;
+
+        if !self.will_save_wait_until {
+            return Ok(None);
+        }
+
+        Ok(Some(WillSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: document.uri.clone() },
+            reason,
+        }))
+    }
+
+    /// Signals that the document at `uri` was saved.
+    pub fn did_save(&self, uri: &DocumentUri) -> Result<Option<SyncNotification>, SyncError> {
+        let document = self.documents.get(uri).ok_or_else(|| SyncError::NotOpen(uri.clone()))?;
+
+        if !self.save {
+            return Ok(None);
+        }
+
+        Ok(Some(SyncNotification::DidSave(DidSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: document.uri.clone() },
+            text: if self.include_text {
+                Elective::Present(document.text.clone())
+            } else {
+                Elective::Absent
+            },
+        })))
+    }
+
+    /// Closes the document at `uri`.
+    pub fn close(&mut self, uri: &DocumentUri) -> Result<Option<SyncNotification>, SyncError> {
+        let document = self.documents.remove(uri).ok_or_else(|| SyncError::NotOpen(uri.clone()))?;
+
+        if !self.open_close {
+            return Ok(None);
+        }
+
+        Ok(Some(SyncNotification::DidClose(DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: document.uri },
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::serde_json::{self, json, Value};
+
+    fn uri() -> DocumentUri {
+        DocumentUri::parse("file:///a").unwrap()
+    }
+
+    /// Returns a `Synchronizer` with the text document sync capability `provider` and an open
+    /// document containing `a\nb`.
+    fn synchronizer(provider: Value) -> (Synchronizer, Option<SyncNotification>) {
+        let mut synchronizer = Synchronizer::new(&serde_json::from_value(provider).unwrap());
+        let notification = synchronizer
+            .open(TextDocumentItem {
+                uri: uri(),
+                text: "a\nb".to_string(),
+                ..TextDocumentItem::default()
+            })
+            .unwrap();
+
+        (synchronizer, notification)
+    }
+
+    /// Returns the content changes of the change notification `notification` as JSON.
+    fn changes(notification: Option<SyncNotification>) -> Value {
+        match notification {
+            Some(SyncNotification::DidChange(params)) => {
+                assert_eq!(params.text_document().version, Some(1));
+                serde_json::to_value(params.content_changes()).unwrap()
+            }
+            notification => panic!("expected a change notification, found {:?}", notification),
+        }
+    }
+
+    #[test]
+    fn sync_kind_none() {
+        let (mut synchronizer, opened) = synchronizer(json!(0));
+
+        assert!(opened.is_none());
+        assert!(synchronizer.change(&uri(), "a\nc".to_string()).unwrap().is_none());
+        assert_eq!(synchronizer.get(&uri()).unwrap().text, "a\nc");
+        assert!(synchronizer.did_save(&uri()).unwrap().is_none());
+        assert!(synchronizer.close(&uri()).unwrap().is_none());
+    }
+
+    #[test]
+    fn sync_kind_full() {
+        let (mut synchronizer, opened) = synchronizer(json!(1));
+
+        assert!(matches!(opened, Some(SyncNotification::DidOpen(_))));
+        assert_eq!(
+            changes(synchronizer.change(&uri(), "a\nc".to_string()).unwrap()),
+            json!([{"text": "a\nc"}])
+        );
+        assert!(matches!(synchronizer.close(&uri()).unwrap(), Some(SyncNotification::DidClose(_))));
+        assert_eq!(synchronizer.close(&uri()).unwrap_err(), SyncError::NotOpen(uri()));
+    }
+
+    #[test]
+    fn sync_kind_incremental() {
+        let (mut synchronizer, _) = synchronizer(json!({"openClose": true, "change": 2}));
+
+        assert_eq!(
+            changes(synchronizer.change(&uri(), "a\nc".to_string()).unwrap()),
+            json!([{"range": {"start": {"line": 1, "character": 0}, "end": {"line": 1, "character": 1}}, "text": "c"}])
+        );
+        assert!(synchronizer.change(&uri(), "a\nc".to_string()).unwrap().is_none());
+        assert_eq!(synchronizer.get(&uri()).unwrap().version, 1);
+    }
+
+    #[test]
+    fn did_save_include_text() {
+        let text = |provider| match synchronizer(provider).0.did_save(&uri()).unwrap() {
+            Some(SyncNotification::DidSave(params)) => params.text.as_option().cloned(),
+            notification => panic!("expected a save notification, found {:?}", notification),
+        };
+
+        assert_eq!(text(json!({"save": {"includeText": true}})), Some("a\nb".to_string()));
+        assert_eq!(text(json!({"save": true})), None);
+        assert_eq!(text(json!(2)), None);
+        assert!(synchronizer(json!({"save": false})).0.did_save(&uri()).unwrap().is_none());
+    }
+}
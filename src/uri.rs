@@ -0,0 +1,572 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::str::FromStr;
+
+/// Characters other than unreserved characters that are not percent-encoded in a component.
+const COMPONENT_DELIMITERS: &[u8] = b"!$&'()*+,;=:@/?#[]";
+/// Characters other than unreserved characters that are not percent-encoded in a path segment
+/// created from a file path.
+const SEGMENT_DELIMITERS: &[u8] = b"!$&'()*+,;=@";
+
+/// An error that occurs while converting to or from a `DocumentUri`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UriError {
+    /// The string does not start with a valid scheme.
+    InvalidScheme(String),
+    /// A `%` in the string is not followed by two hexadecimal digits.
+    InvalidPercentEncoding(String),
+    /// The URI does not identify a local file.
+    NotFile(DocumentUri),
+    /// The path is not absolute or cannot be represented as a URI.
+    InvalidPath(PathBuf),
+}
+
+impl Display for UriError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UriError::InvalidScheme(uri) => write!(f, "`{}` does not have a valid scheme", uri),
+            UriError::InvalidPercentEncoding(uri) => write!(f, "`{}` has an invalid percent-encoding", uri),
+            UriError::NotFile(uri) => write!(f, "`{}` is not a local file URI", uri),
+            UriError::InvalidPath(path) => write!(f, "`{}` cannot be converted to a URI", path.display()),
+        }
+    }
+}
+
+impl Error for UriError {}
+
+/// A URI that identifies a document.
+///
+/// `DocumentUri`s are compared by their normalized form, in which the scheme and authority are
+/// lowercase, percent-encoding is canonical, the drive letter of a file URI is lowercase and
+/// the `localhost` authority of a file URI is omitted and dot segments of the path are removed.
+/// The original string is kept for serialization.
+///
+/// The default `DocumentUri` is `file:///`, the root folder of the local file system.
+#[derive(Clone)]
+pub struct DocumentUri {
+    /// The URI as parsed.
+    original: String,
+    /// The normalized URI.
+    normalized: String,
+}
+
+impl DocumentUri {
+    /// Parses `uri`.
+    pub fn parse(uri: &str) -> Result<Self, UriError> {
+        let (scheme, rest) = match uri.find(':') {
+            Some(index) if is_scheme(&uri[..index]) => (uri[..index].to_ascii_lowercase(), &uri[index + 1..]),
+            _ => return Err(UriError::InvalidScheme(uri.to_string())),
+        };
+
+        if !has_valid_escapes(rest) {
+            return Err(UriError::InvalidPercentEncoding(uri.to_string()));
+        }
+
+        let (authority, rest) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+
+                (Some(&rest[..end]), &rest[end..])
+            }
+            None => (None, rest),
+        };
+        let mut normalized = scheme.clone();
+
+        normalized.push(':');
+
+        if let Some(authority) = authority {
+            let authority = normalize(authority).to_ascii_lowercase();
+
+            normalized.push_str("//");
+
+            if !(scheme == "file" && authority == "localhost") {
+                normalized.push_str(&authority);
+            }
+        }
+
+        let mut path = normalize(rest);
+
+        if scheme == "file" {
+            normalize_drive_letter(&mut path);
+        }
+
+        // Only a hierarchical path has dot segments.
+        if path.starts_with('/') {
+            let end = path.find(['?', '#']).unwrap_or(path.len());
+
+            path.replace_range(..end, &remove_dot_segments(&path[..end]));
+        }
+
+        normalized.push_str(&path);
+
+        Ok(DocumentUri {
+            original: uri.to_string(),
+            normalized,
+        })
+    }
+
+    /// Creates a file `DocumentUri` from the absolute `path`.
+    ///
+    /// `..` components of `path` remove the preceding component.
+    pub fn from_file_path<P: AsRef<Path>>(path: P) -> Result<Self, UriError> {
+        let path = path.as_ref();
+        let invalid = || UriError::InvalidPath(path.to_path_buf());
+
+        if !path.is_absolute() {
+            return Err(invalid());
+        }
+
+        let mut uri = String::from("file://");
+        let mut segments = Vec::new();
+
+        for component in path.components() {
+            match component {
+                Component::Prefix(prefix) => match prefix.kind() {
+                    Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                        uri.push('/');
+                        uri.push(char::from(letter).to_ascii_lowercase());
+                        uri.push(':');
+                    }
+                    Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                        uri.push_str(&encode(server.to_str().ok_or_else(invalid)?.as_bytes()));
+                        uri.push('/');
+                        uri.push_str(&encode(share.to_str().ok_or_else(invalid)?.as_bytes()));
+                    }
+                    _ => return Err(invalid()),
+                },
+                Component::RootDir | Component::CurDir => {}
+                Component::ParentDir => {
+                    segments.pop();
+                }
+                Component::Normal(segment) => segments.push(encode(&segment_bytes(segment).ok_or_else(invalid)?)),
+            }
+        }
+
+        for segment in segments {
+            uri.push('/');
+            uri.push_str(&segment);
+        }
+
+        if uri.len() == "file://".len() {
+            uri.push('/');
+        }
+
+        DocumentUri::parse(&uri)
+    }
+
+    /// Returns the path of the local file identified by the `DocumentUri`.
+    pub fn to_file_path(&self) -> Result<PathBuf, UriError> {
+        let not_file = || UriError::NotFile(self.clone());
+        let rest = self.normalized.strip_prefix("file:").ok_or_else(not_file)?;
+        let rest = &rest[..rest.find(['?', '#']).unwrap_or(rest.len())];
+        let (host, path) = match rest.strip_prefix("//") {
+            Some(rest) => rest.split_at(rest.find('/').unwrap_or(rest.len())),
+            None => ("", rest),
+        };
+
+        if !path.starts_with('/') {
+            return Err(not_file());
+        }
+
+        to_path(host, decode(path)).ok_or_else(not_file)
+    }
+
+    /// Returns the URI as parsed.
+    pub fn as_str(&self) -> &str {
+        &self.original
+    }
+
+    /// Returns the normalized URI.
+    pub fn normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    /// Returns the scheme of the URI in lowercase.
+    pub fn scheme(&self) -> &str {
+        &self.normalized[..self.normalized.find(':').unwrap_or(0)]
+    }
+
+    /// Returns if the `DocumentUri` is `folder` or is within `folder`.
+    pub fn is_within(&self, folder: &DocumentUri) -> bool {
+        let prefix = folder.normalized.trim_end_matches('/');
+
+        self.normalized == folder.normalized
+            || (self.normalized.starts_with(prefix) && self.normalized[prefix.len()..].starts_with('/'))
+    }
+
+    /// Returns the `DocumentUri` with `from` replaced by `to` if it is within `from`.
+    pub fn rebase(&self, from: &DocumentUri, to: &DocumentUri) -> Option<Self> {
+        if !self.is_within(from) {
+            return None;
+        }
+
+        if self == from {
+            return Some(to.clone());
+        }
+
+        let suffix = &self.normalized[from.normalized.trim_end_matches('/').len()..];
+
+        Some(DocumentUri {
+            original: format!("{}{}", to.original.trim_end_matches('/'), suffix),
+            normalized: format!("{}{}", to.normalized.trim_end_matches('/'), suffix),
+        })
+    }
+}
+
+impl Default for DocumentUri {
+    fn default() -> Self {
+        DocumentUri {
+            original: String::from("file:///"),
+            normalized: String::from("file:///"),
+        }
+    }
+}
+
+impl fmt::Debug for DocumentUri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DocumentUri").field(&self.original).finish()
+    }
+}
+
+impl Display for DocumentUri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.original)
+    }
+}
+
+impl PartialEq for DocumentUri {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl Eq for DocumentUri {}
+
+impl Hash for DocumentUri {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+}
+
+impl PartialOrd for DocumentUri {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DocumentUri {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized.cmp(&other.normalized)
+    }
+}
+
+impl FromStr for DocumentUri {
+    type Err = UriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DocumentUri::parse(s)
+    }
+}
+
+impl Serialize for DocumentUri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.original)
+    }
+}
+
+impl<'de> Deserialize<'de> for DocumentUri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DocumentUri::parse(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Returns if `scheme` is a valid URI scheme.
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+}
+
+/// Returns if each `%` in `text` is followed by two hexadecimal digits.
+fn has_valid_escapes(text: &str) -> bool {
+    let bytes = text.as_bytes();
+
+    bytes.iter().enumerate().filter(|(_, &byte)| byte == b'%').all(|(index, _)| {
+        bytes.len() > index + 2 && bytes[index + 1].is_ascii_hexdigit() && bytes[index + 2].is_ascii_hexdigit()
+    })
+}
+
+/// Returns if `byte` is an unreserved URI character.
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~".contains(&byte)
+}
+
+/// Returns `text` with unreserved characters decoded and all other characters encoded with
+/// uppercase hexadecimal digits, except for delimiters that are not encoded.
+///
+/// Each `%` in `text` must be followed by two hexadecimal digits.
+fn normalize(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut normalized = String::with_capacity(text.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let (byte, is_escaped) = if bytes[index] == b'%' {
+            index += 2;
+            (hex_value(bytes[index - 1]) * 16 + hex_value(bytes[index]), true)
+        } else {
+            (bytes[index], false)
+        };
+
+        if is_unreserved(byte) || (!is_escaped && COMPONENT_DELIMITERS.contains(&byte)) {
+            normalized.push(char::from(byte));
+        } else {
+            normalized.push_str(&format!("%{:02X}", byte));
+        }
+
+        index += 1;
+    }
+
+    normalized
+}
+
+/// Returns the value of the hexadecimal digit `digit`.
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+/// Replaces a leading Windows drive letter in `path` with its lowercase form followed by `:`.
+fn normalize_drive_letter(path: &mut String) {
+    let bytes = path.as_bytes();
+
+    if bytes.len() >= 2 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() {
+        let length = if bytes[2..].starts_with(b":") {
+            1
+        } else if bytes[2..].starts_with(b"%3A") {
+            3
+        } else {
+            return;
+        };
+
+        if bytes.len() == 2 + length || bytes[2 + length] == b'/' {
+            let letter = char::from(bytes[1].to_ascii_lowercase());
+
+            path.replace_range(1..2 + length, &format!("{}:", letter));
+        }
+    }
+}
+
+/// Returns `path` with its `.` and `..` segments removed as described by RFC 3986.
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../").or_else(|| input.strip_prefix("./")) {
+            input = rest;
+        } else if input.starts_with("/./") || input == "/." {
+            input = if input == "/." { "/" } else { &input[2..] };
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            output.truncate(output.rfind('/').unwrap_or(0));
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let end = input[1..].find('/').map_or(input.len(), |index| index + 1);
+
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+
+    output
+}
+
+/// Returns `bytes` with all characters that are not unreserved or segment delimiters encoded.
+fn encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            if is_unreserved(byte) || SEGMENT_DELIMITERS.contains(&byte) {
+                char::from(byte).to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+/// Returns `text` with all percent-encoded characters decoded.
+fn decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            decoded.push(hex_value(bytes[index + 1]) * 16 + hex_value(bytes[index + 2]));
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    decoded
+}
+
+/// Returns the bytes of a path segment.
+#[cfg(unix)]
+fn segment_bytes(segment: &std::ffi::OsStr) -> Option<Vec<u8>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Some(segment.as_bytes().to_vec())
+}
+
+/// Returns the bytes of a path segment.
+#[cfg(not(unix))]
+fn segment_bytes(segment: &std::ffi::OsStr) -> Option<Vec<u8>> {
+    segment.to_str().map(|segment| segment.as_bytes().to_vec())
+}
+
+/// Returns the path of a file URI with `host` and the decoded `path`.
+#[cfg(unix)]
+fn to_path(host: &str, path: Vec<u8>) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    if !host.is_empty() {
+        return None;
+    }
+
+    Some(PathBuf::from(OsString::from_vec(path)))
+}
+
+/// Returns the path of a file URI with `host` and the decoded `path`.
+#[cfg(not(unix))]
+fn to_path(host: &str, path: Vec<u8>) -> Option<PathBuf> {
+    let path = String::from_utf8(path).ok()?.replace('/', "\\");
+
+    if !host.is_empty() {
+        return Some(PathBuf::from(format!("\\\\{}{}", host, path)));
+    }
+
+    // A drive letter is preceded by the root of the URI path.
+    let bytes = path.as_bytes();
+
+    if bytes.len() >= 3 && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        Some(PathBuf::from(&path[1..]))
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextDocumentIdentifier;
+    use jsonrpc_core::serde_json::{self, json};
+
+    fn uri(uri: &str) -> DocumentUri {
+        DocumentUri::parse(uri).unwrap()
+    }
+
+    #[test]
+    fn percent_encoding() {
+        assert_eq!(uri("file:///a%7eb%2Fc").normalized(), "file:///a~b%2Fc");
+        assert_eq!(uri("file:///%c3%a9 a"), uri("file:///%C3%A9%20a"));
+        assert_eq!(uri("HTTP://Example.COM/A"), uri("http://example.com/A"));
+        assert_ne!(uri("file:///a/b"), uri("file:///a%2Fb"));
+        assert_eq!(DocumentUri::parse("file:///a%2"), Err(UriError::InvalidPercentEncoding("file:///a%2".to_string())));
+        assert_eq!(DocumentUri::parse("1file:///a"), Err(UriError::InvalidScheme("1file:///a".to_string())));
+    }
+
+    #[test]
+    fn drive_letters() {
+        assert_eq!(uri("file:///C:/a").normalized(), "file:///c:/a");
+        assert_eq!(uri("file:///c%3A/a"), uri("file:///C:/a"));
+        assert_eq!(uri("file://localhost/C:"), uri("file:///c:"));
+        assert_eq!(uri("file:///cd:/a").normalized(), "file:///cd:/a");
+    }
+
+    #[test]
+    fn dot_segments() {
+        assert_eq!(uri("file:///a/./b/../c").normalized(), "file:///a/c");
+        assert_eq!(uri("file:///a/%2E%2E/b").normalized(), "file:///b");
+        assert_eq!(uri("file:///../a?b/../c").normalized(), "file:///a?b/../c");
+        assert_eq!(uri("file:///a/b/..").normalized(), "file:///a/");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_paths() {
+        let path = Path::new("/a b/é%/c");
+        let file_uri = DocumentUri::from_file_path(path).unwrap();
+
+        assert_eq!(file_uri.as_str(), "file:///a%20b/%C3%A9%25/c");
+        assert_eq!(file_uri.to_file_path().unwrap(), path);
+        assert_eq!(DocumentUri::from_file_path("/").unwrap().as_str(), "file:///");
+        assert_eq!(DocumentUri::from_file_path("/a/b/../../c/./d").unwrap().as_str(), "file:///c/d");
+        assert_eq!(DocumentUri::from_file_path("/../a").unwrap().as_str(), "file:///a");
+        assert_eq!(DocumentUri::from_file_path("a"), Err(UriError::InvalidPath(PathBuf::from("a"))));
+        assert_eq!(uri("file://localhost/a").to_file_path().unwrap(), Path::new("/a"));
+        assert_eq!(uri("file://host/a").to_file_path(), Err(UriError::NotFile(uri("file://host/a"))));
+        assert_eq!(uri("untitled:a").to_file_path(), Err(UriError::NotFile(uri("untitled:a"))));
+    }
+
+    #[test]
+    fn is_within() {
+        let folder = uri("file:///a");
+
+        assert!(uri("file:///a").is_within(&folder));
+        assert!(uri("file:///a/b").is_within(&folder));
+        assert!(uri("file:///a/b").is_within(&uri("file:///a/")));
+        assert!(!uri("file:///ab").is_within(&folder));
+        assert!(!uri("file:///a/../b").is_within(&folder));
+        assert!(!uri("http:///a/b").is_within(&folder));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_path_is_not_within_after_parent() {
+        let folder = DocumentUri::from_file_path("/a").unwrap();
+
+        assert!(!DocumentUri::from_file_path("/a/../b").unwrap().is_within(&folder));
+    }
+
+    #[test]
+    fn rebase() {
+        let (from, to) = (uri("file:///a"), uri("file:///x/y/"));
+
+        assert_eq!(uri("file:///a").rebase(&from, &to), Some(to.clone()));
+        assert_eq!(uri("file:///a/b/c").rebase(&from, &to), Some(uri("file:///x/y/b/c")));
+        assert_eq!(uri("file:///a/b").rebase(&from, &to).unwrap().as_str(), "file:///x/y/b");
+        assert_eq!(uri("file:///ab").rebase(&from, &to), None);
+    }
+
+    #[test]
+    fn serde() {
+        let original = uri("file:///C:/a%7e");
+
+        assert_eq!(serde_json::to_value(&original).unwrap(), json!("file:///C:/a%7e"));
+        assert_eq!(
+            serde_json::from_value::<DocumentUri>(json!("file:///C:/a%7e")).unwrap().as_str(),
+            "file:///C:/a%7e"
+        );
+        assert!(serde_json::from_value::<DocumentUri>(json!("")).is_err());
+
+        let identifier = serde_json::to_value(TextDocumentIdentifier::default()).unwrap();
+
+        assert_eq!(identifier, json!({"uri": "file:///"}));
+        assert!(serde_json::from_value::<TextDocumentIdentifier>(identifier).is_ok());
+    }
+}
@@ -0,0 +1,634 @@
+use crate::{
+    ApplyWorkspaceEditResponse, CreateFile, DeleteFile, DocumentChangeOperation, DocumentChanges, Elective,
+    FailureHandlingKind, LanguageId, Position, PositionEncodingKind, Range, RenameFile, ResourceOperation,
+    ResourceOperationKind, TextDocumentEdit, TextDocumentItem, TextEdit, VersionedTextDocumentIdentifier, WorkspaceEdit,
+};
+use crate::uri::DocumentUri;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// An error that prevents an operation of a `WorkspaceEdit` from being applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ApplyError {
+    /// No document exists at the URI.
+    NotFound(DocumentUri),
+    /// A document already exists at the URI.
+    AlreadyExists(DocumentUri),
+    /// The folder at the URI contains documents but the delete is not recursive.
+    FolderNotEmpty(DocumentUri),
+    /// The version of the document does not match the version of the edit.
+    VersionMismatch {
+        /// URI of the document.
+        uri: DocumentUri,
+        /// Version specified by the edit.
+        expected: i64,
+        /// Version of the document.
+        actual: i64,
+    },
+    /// The `Range` of an edit is not within the document.
+    InvalidRange(Range),
+    /// Two edits of the same document overlap.
+    OverlappingEdits(Range, Range),
+    /// The kind of the resource operation is not supported.
+    Unsupported(ResourceOperationKind),
+    /// The URI does not denote a file.
+    InvalidUri(DocumentUri),
+    /// Accessing the file failed.
+    Io(String),
+}
+
+impl Display for ApplyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyError::NotFound(uri) => write!(f, "`{}` does not exist", uri),
+            ApplyError::AlreadyExists(uri) => write!(f, "`{}` already exists", uri),
+            ApplyError::FolderNotEmpty(uri) => write!(f, "folder `{}` is not empty", uri),
+            ApplyError::VersionMismatch { uri, expected, actual } => {
+                write!(f, "`{}` is at version {} instead of {}", uri, actual, expected)
+            }
+            ApplyError::InvalidRange(range) => write!(f, "range {:?} is not within the document", range),
+            ApplyError::OverlappingEdits(first, second) => write!(f, "edits at {:?} and {:?} overlap", first, second),
+            ApplyError::Unsupported(kind) => write!(f, "resource operation {:?} is not supported", kind),
+            ApplyError::InvalidUri(uri) => write!(f, "`{}` is not a file URI", uri),
+            ApplyError::Io(message) => write!(f, "io error: {}", message),
+        }
+    }
+}
+
+impl Error for ApplyError {}
+
+/// The outcome of an operation of a `WorkspaceEdit`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The operation was applied.
+    Applied,
+    /// The operation was skipped due to its `ignore_if_exists` or `ignore_if_not_exists` option.
+    Ignored,
+    /// The operation failed.
+    Failed(ApplyError),
+    /// The operation was applied and then reverted due to a later failure.
+    RolledBack,
+    /// The operation was not attempted due to an earlier failure.
+    NotAttempted,
+}
+
+/// The outcome of each operation of a `WorkspaceEdit`, in order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApplyReport {
+    /// The outcomes.
+    pub outcomes: Vec<Outcome>,
+}
+
+impl ApplyReport {
+    /// Returns the index and error of the operation that failed.
+    pub fn failure(&self) -> Option<(usize, &ApplyError)> {
+        self.outcomes.iter().enumerate().find_map(|(index, outcome)| match outcome {
+            Outcome::Failed(error) => Some((index, error)),
+            _ => None,
+        })
+    }
+
+    /// Returns if every operation was applied or ignored.
+    pub fn is_applied(&self) -> bool {
+        self.failure().is_none()
+    }
+}
+
+impl From<&ApplyReport> for ApplyWorkspaceEditResponse {
+    fn from(value: &ApplyReport) -> Self {
+        match value.failure() {
+            Some((index, error)) => ApplyWorkspaceEditResponse {
+                applied: false,
+                failure_reason: Elective::Present(error.to_string()),
+                failed_change: Elective::Present(index as u64),
+            },
+            None => ApplyWorkspaceEditResponse {
+                applied: true,
+                failure_reason: Elective::Absent,
+                failed_change: Elective::Absent,
+            },
+        }
+    }
+}
+
+/// Reverts an applied operation.
+#[derive(Debug)]
+pub(crate) enum Undo {
+    /// Restores the document before its text was edited.
+    Text(TextDocumentItem),
+    /// Removes a created document and restores the document it replaced.
+    Create(DocumentUri, Option<TextDocumentItem>),
+    /// Moves renamed documents back and restores the documents they replaced.
+    Rename(Vec<(DocumentUri, DocumentUri)>, Vec<TextDocumentItem>),
+    /// Restores deleted documents.
+    Delete(Vec<TextDocumentItem>),
+}
+
+/// An in-memory set of text documents to which `WorkspaceEdit`s are applied.
+///
+/// A folder is represented by the documents whose URIs are prefixed by the URI of the folder.
+#[derive(Clone, Debug, Default)]
+pub struct Workspace {
+    /// Documents by URI.
+    documents: HashMap<DocumentUri, TextDocumentItem>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Workspace::default()
+    }
+
+    /// Inserts `document`, returning the document previously at its URI.
+    pub fn insert(&mut self, document: TextDocumentItem) -> Option<TextDocumentItem> {
+        self.documents.insert(document.uri.clone(), document)
+    }
+
+    /// Returns the document at `uri`.
+    pub fn get(&self, uri: &DocumentUri) -> Option<&TextDocumentItem> {
+        self.documents.get(uri)
+    }
+
+    /// Removes and returns the document at `uri`.
+    pub fn remove(&mut self, uri: &DocumentUri) -> Option<TextDocumentItem> {
+        self.documents.remove(uri)
+    }
+
+    /// Returns an iterator over the documents.
+    pub fn documents(&self) -> impl Iterator<Item = &TextDocumentItem> {
+        self.documents.values()
+    }
+
+    /// Applies the operations of `edit` in order, handling a failure according to
+    /// `failure_handling`.
+    ///
+    /// Text edits of a document increment its version.
+    pub fn apply(&mut self, edit: &WorkspaceEdit, failure_handling: FailureHandlingKind) -> ApplyReport {
+        apply_operations(self, &operations(edit), failure_handling)
+    }
+
+    /// Applies the text edits of `edit`.
+    fn edit(&mut self, edit: &TextDocumentEdit) -> Result<Option<Undo>, ApplyError> {
+        let uri = &edit.text_document.uri;
+        let document = self.documents.get_mut(uri).ok_or_else(|| ApplyError::NotFound(uri.clone()))?;
+
+        if let Some(expected) = edit.text_document.version {
+            if expected != document.version {
+                return Err(ApplyError::VersionMismatch {
+                    uri: uri.clone(),
+                    expected,
+                    actual: document.version,
+                });
+            }
+        }
+
+        let text = apply_text_edits(&document.text, &edit.edits)?;
+        let previous = document.clone();
+
+        document.text = text;
+        document.increment_version();
+        Ok(Some(Undo::Text(previous)))
+    }
+
+    /// Creates the document of `create`.
+    fn create(&mut self, create: &CreateFile) -> Result<Option<Undo>, ApplyError> {
+        let options = create.options.as_option().copied().unwrap_or_default();
+        let replaced = self.documents.get(&create.uri).cloned();
+
+        if replaced.is_some() && !options.overwrite {
+            if options.ignore_if_exists {
+                return Ok(None);
+            }
+
+            return Err(ApplyError::AlreadyExists(create.uri.clone()));
+        }
+
+        self.insert(TextDocumentItem {
+            uri: create.uri.clone(),
+            language_id: create
+                .uri
+                .to_file_path()
+                .ok()
+                .and_then(LanguageId::from_path)
+                .unwrap_or_default(),
+            version: 0,
+            text: String::new(),
+        });
+        Ok(Some(Undo::Create(create.uri.clone(), replaced)))
+    }
+
+    /// Renames the document or folder of `rename`.
+    fn rename(&mut self, rename: &RenameFile) -> Result<Option<Undo>, ApplyError> {
+        let options = rename.options.as_option().copied().unwrap_or_default();
+        let moves = self
+            .uris_within(&rename.old_uri)
+            .into_iter()
+            .filter_map(|uri| {
+                let new_uri = uri.rebase(&rename.old_uri, &rename.new_uri)?;
+
+                Some((uri, new_uri))
+            })
+            .collect::<Vec<_>>();
+
+        if moves.is_empty() {
+            return Err(ApplyError::NotFound(rename.old_uri.clone()));
+        }
+
+        // The renamed documents do not replace themselves.
+        let existing = self
+            .uris_within(&rename.new_uri)
+            .into_iter()
+            .filter(|uri| !uri.is_within(&rename.old_uri))
+            .collect::<Vec<_>>();
+
+        if !existing.is_empty() && !options.overwrite {
+            if options.ignore_if_exists {
+                return Ok(None);
+            }
+
+            return Err(ApplyError::AlreadyExists(rename.new_uri.clone()));
+        }
+
+        // The renamed documents are removed first so that they are not removed as replaced.
+        let moved = moves
+            .iter()
+            .filter_map(|(old_uri, new_uri)| Some((self.documents.remove(old_uri)?, new_uri)))
+            .collect::<Vec<_>>();
+        let replaced = existing.iter().filter_map(|uri| self.documents.remove(uri)).collect();
+
+        for (mut document, new_uri) in moved {
+            document.uri = new_uri.clone();
+            self.insert(document);
+        }
+
+        Ok(Some(Undo::Rename(moves, replaced)))
+    }
+
+    /// Deletes the document or folder of `delete`.
+    fn delete(&mut self, delete: &DeleteFile) -> Result<Option<Undo>, ApplyError> {
+        let options = delete.options.as_option().copied().unwrap_or_default();
+        let uris = self.uris_within(&delete.uri);
+
+        if uris.is_empty() {
+            if options.ignore_if_not_exists {
+                return Ok(None);
+            }
+
+            return Err(ApplyError::NotFound(delete.uri.clone()));
+        }
+
+        if !options.recursive && uris.iter().any(|uri| *uri != delete.uri) {
+            return Err(ApplyError::FolderNotEmpty(delete.uri.clone()));
+        }
+
+        Ok(Some(Undo::Delete(
+            uris.iter().filter_map(|uri| self.documents.remove(uri)).collect(),
+        )))
+    }
+
+    /// Reverts an applied operation.
+    fn undo(&mut self, undo: Undo) {
+        match undo {
+            Undo::Text(previous) => {
+                self.insert(previous);
+            }
+            Undo::Create(uri, replaced) => {
+                self.documents.remove(&uri);
+
+                if let Some(document) = replaced {
+                    self.insert(document);
+                }
+            }
+            Undo::Rename(moves, replaced) => {
+                // A new URI may be the old URI of another move, so all are removed before any is
+                // moved back.
+                let moved = moves
+                    .into_iter()
+                    .filter_map(|(old_uri, new_uri)| Some((self.documents.remove(&new_uri)?, old_uri)))
+                    .collect::<Vec<_>>();
+
+                for (mut document, old_uri) in moved {
+                    document.uri = old_uri;
+                    self.insert(document);
+                }
+
+                for document in replaced {
+                    self.insert(document);
+                }
+            }
+            Undo::Delete(documents) => {
+                for document in documents {
+                    self.insert(document);
+                }
+            }
+        }
+    }
+
+    /// Returns the URIs of the document at `uri` and the documents within the folder at `uri`.
+    fn uris_within(&self, uri: &DocumentUri) -> Vec<DocumentUri> {
+        self.documents.keys().filter(|key| key.is_within(uri)).cloned().collect()
+    }
+}
+
+/// Returns the operations of `edit` in order.
+///
+/// `WorkspaceEdit::document_changes` is preferred over `WorkspaceEdit::changes`, which are ordered
+/// by URI.
+pub fn operations(edit: &WorkspaceEdit) -> Vec<DocumentChangeOperation> {
+    match (&edit.document_changes, &edit.changes) {
+        (Elective::Present(DocumentChanges::Edits(edits)), _) => {
+            edits.iter().cloned().map(DocumentChangeOperation::Edit).collect()
+        }
+        (Elective::Present(DocumentChanges::Operations(operations)), _) => operations.clone(),
+        (Elective::Absent, Elective::Present(changes)) => {
+            let mut uris = changes.keys().collect::<Vec<_>>();
+
+            uris.sort();
+            uris.into_iter()
+                .map(|uri| {
+                    DocumentChangeOperation::Edit(TextDocumentEdit {
+                        text_document: VersionedTextDocumentIdentifier {
+                            uri: uri.clone(),
+                            version: None,
+                        },
+                        edits: changes[uri].clone(),
+                    })
+                })
+                .collect()
+        }
+        (Elective::Absent, Elective::Absent) => Vec::new(),
+    }
+}
+
+/// Applies and reverts the operations of a `WorkspaceEdit`.
+pub(crate) trait Applier {
+    /// Reverts an applied operation.
+    type Undo;
+
+    /// Applies `operation`, returning how to revert it or `Option::None` if it is ignored.
+    fn apply_operation(&mut self, operation: &DocumentChangeOperation) -> Result<Option<Self::Undo>, ApplyError>;
+
+    /// Reverts an applied operation, returning if it was reverted.
+    fn undo_operation(&mut self, undo: Self::Undo) -> bool;
+}
+
+impl Applier for Workspace {
+    type Undo = Undo;
+
+    fn apply_operation(&mut self, operation: &DocumentChangeOperation) -> Result<Option<Undo>, ApplyError> {
+        match operation {
+            DocumentChangeOperation::Edit(edit) => self.edit(edit),
+            DocumentChangeOperation::Op(ResourceOperation::Create(create)) => self.create(create),
+            DocumentChangeOperation::Op(ResourceOperation::Rename(rename)) => self.rename(rename),
+            DocumentChangeOperation::Op(ResourceOperation::Delete(delete)) => self.delete(delete),
+        }
+    }
+
+    fn undo_operation(&mut self, undo: Undo) -> bool {
+        self.undo(undo);
+        true
+    }
+}
+
+/// Applies `operations` in order with `applier` until one fails, handling the failure according
+/// to `failure_handling`.
+pub(crate) fn apply_operations<A: Applier>(
+    applier: &mut A,
+    operations: &[DocumentChangeOperation],
+    failure_handling: FailureHandlingKind,
+) -> ApplyReport {
+    let mut outcomes = Vec::with_capacity(operations.len());
+    let mut undos = Vec::new();
+    let mut has_failed = false;
+
+    for operation in operations {
+        if has_failed {
+            outcomes.push(Outcome::NotAttempted);
+            continue;
+        }
+
+        match applier.apply_operation(operation) {
+            Ok(Some(undo)) => {
+                undos.push((outcomes.len(), undo));
+                outcomes.push(Outcome::Applied);
+            }
+            Ok(None) => outcomes.push(Outcome::Ignored),
+            Err(error) => {
+                has_failed = true;
+                outcomes.push(Outcome::Failed(error));
+            }
+        }
+    }
+
+    let rolls_back = match failure_handling {
+        FailureHandlingKind::Abort => false,
+        FailureHandlingKind::Transactional | FailureHandlingKind::Undo => true,
+        // Only an edit without resource operations is transactional; otherwise, the failure is
+        // handled like `FailureHandlingKind::Abort`.
+        FailureHandlingKind::TextOnlyTransactional => {
+            !operations.iter().any(|operation| matches!(operation, DocumentChangeOperation::Op(_)))
+        }
+    };
+
+    if has_failed && rolls_back {
+        for (index, undo) in undos.into_iter().rev() {
+            // An undo that fails leaves the operation applied.
+            if applier.undo_operation(undo) {
+                outcomes[index] = Outcome::RolledBack;
+            }
+        }
+    }
+
+    ApplyReport { outcomes }
+}
+
+/// Returns `text` after applying `edits`, which all refer to `text`.
+///
+/// Edits that insert at the same `Position` are applied in order.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> Result<String, ApplyError> {
+    let mut spans = edits
+        .iter()
+        .map(|edit| {
+            let start = offset_at(text, edit.range.start);
+            let end = offset_at(text, edit.range.end);
+
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => Ok((start, end, edit)),
+                _ => Err(ApplyError::InvalidRange(edit.range)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Stable sort keeps insertions at the same offset in order and before a replacement that
+    // starts there.
+    spans.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut result = String::with_capacity(text.len());
+    let mut offset = 0;
+    let mut previous: Option<&TextEdit> = None;
+
+    for (start, end, edit) in spans {
+        if start < offset {
+            return Err(ApplyError::OverlappingEdits(
+                previous.map_or(edit.range, |previous| previous.range),
+                edit.range,
+            ));
+        }
+
+        result.push_str(&text[offset..start]);
+        result.push_str(&edit.new_text);
+        offset = end;
+        previous = Some(edit);
+    }
+
+    result.push_str(&text[offset..]);
+    Ok(result)
+}
+
+/// Returns the byte offset of `position` in `text`.
+///
+/// `Position::character` is in UTF-16 code units and defaults to the line length if greater.
+/// Returns `Option::None` if `Position::line` is not within `text`.
+fn offset_at(text: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+
+    for _ in 0..position.line {
+        let rest = &text[line_start..];
+        let terminator = rest.find(['\n', '\r'])?;
+
+        line_start += terminator + if rest[terminator..].starts_with("\r\n") { 2 } else { 1 };
+    }
+
+    let line = &text[line_start..];
+    let line = &line[..line.find(['\n', '\r']).unwrap_or(line.len())];
+
+    Some(line_start + PositionEncodingKind::Utf16.byte_offset(line, position.character))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use jsonrpc_core::serde_json::{self, json};
+
+    /// Returns a workspace with a document at `file:///a` containing `a`.
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+
+        workspace.insert(TextDocumentItem {
+            uri: uri("file:///a"),
+            language_id: LanguageId::default(),
+            version: 0,
+            text: "a".to_string(),
+        });
+        workspace
+    }
+
+    /// Returns an operation that replaces the first character of the document at `uri` with `text`.
+    pub(crate) fn edit(uri: &str, text: &str) -> serde_json::Value {
+        json!({
+            "textDocument": {"uri": uri, "version": null},
+            "edits": [{
+                "range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 1}},
+                "newText": text,
+            }],
+        })
+    }
+
+    /// Returns a `WorkspaceEdit` of `operations`.
+    pub(crate) fn workspace_edit(operations: Vec<serde_json::Value>) -> WorkspaceEdit {
+        serde_json::from_value(json!({ "documentChanges": operations })).unwrap()
+    }
+
+    fn uri(uri: &str) -> DocumentUri {
+        DocumentUri::parse(uri).unwrap()
+    }
+
+    fn text_edit(start: u64, end: u64, new_text: &str) -> TextEdit {
+        TextEdit::new(Range::new(Position::new(0, start), Position::new(0, end)), new_text.to_string())
+    }
+
+    #[test]
+    fn text_only_transactional_with_resource_operations_aborts() {
+        let mut workspace = workspace();
+        let edit = workspace_edit(vec![
+            json!({"kind": "create", "uri": "file:///b"}),
+            edit("file:///a", "x"),
+            edit("file:///c", "x"),
+        ]);
+        let report = workspace.apply(&edit, FailureHandlingKind::TextOnlyTransactional);
+
+        assert_eq!(report.outcomes[..2], [Outcome::Applied, Outcome::Applied]);
+        assert!(workspace.get(&uri("file:///b")).is_some());
+        assert_eq!(workspace.get(&uri("file:///a")).unwrap().text, "x");
+    }
+
+    #[test]
+    fn text_only_transactional_without_resource_operations_rolls_back() {
+        let mut workspace = workspace();
+        let edit = workspace_edit(vec![edit("file:///a", "x"), edit("file:///c", "x")]);
+        let report = workspace.apply(&edit, FailureHandlingKind::TextOnlyTransactional);
+
+        assert_eq!(report.outcomes[0], Outcome::RolledBack);
+        assert_eq!(workspace.get(&uri("file:///a")).unwrap().text, "a");
+    }
+
+    #[test]
+    fn insertion_at_start_of_replacement() {
+        let replace = text_edit(1, 3, "X");
+        let insert = text_edit(1, 1, "Y");
+
+        assert_eq!(apply_text_edits("abcd", &[replace.clone(), insert.clone()]).unwrap(), "aYXd");
+        assert_eq!(apply_text_edits("abcd", &[insert, replace]).unwrap(), "aYXd");
+    }
+
+    #[test]
+    fn overlapping_edits() {
+        assert!(apply_text_edits("abcd", &[text_edit(0, 2, ""), text_edit(1, 3, "")]).is_err());
+    }
+
+    #[test]
+    fn rename_onto_itself() {
+        let mut workspace = workspace();
+        let edit = workspace_edit(vec![
+            json!({"kind": "rename", "oldUri": "file:///a", "newUri": "file:///a", "options": {"overwrite": true}}),
+        ]);
+
+        assert_eq!(workspace.apply(&edit, FailureHandlingKind::Abort).outcomes, [Outcome::Applied]);
+        assert_eq!(workspace.get(&uri("file:///a")).unwrap().text, "a");
+    }
+
+    #[test]
+    fn rename_into_ancestor_with_overwrite() {
+        let mut workspace = Workspace::new();
+
+        for (uri, text) in [("file:///a/b", "b"), ("file:///a/c", "c"), ("file:///a/b/d", "d")] {
+            workspace.insert(TextDocumentItem {
+                uri: self::uri(uri),
+                language_id: LanguageId::default(),
+                version: 0,
+                text: text.to_string(),
+            });
+        }
+
+        let rename = workspace_edit(vec![
+            json!({"kind": "rename", "oldUri": "file:///a/b", "newUri": "file:///a", "options": {"overwrite": true}}),
+        ]);
+        let rename_back = workspace_edit(vec![
+            json!({"kind": "rename", "oldUri": "file:///a", "newUri": "file:///a/b", "options": {"overwrite": true}}),
+            edit("file:///e", "x"),
+        ]);
+        let report = workspace.apply(&rename, FailureHandlingKind::Abort);
+
+        assert_eq!(report.outcomes[0], Outcome::Applied);
+        assert_eq!(workspace.get(&uri("file:///a")).unwrap().text, "b");
+        assert_eq!(workspace.get(&uri("file:///a/d")).unwrap().text, "d");
+        assert!(workspace.get(&uri("file:///a/c")).is_none());
+        assert!(workspace.get(&uri("file:///a/b")).is_none());
+
+        let report = workspace.apply(&rename_back, FailureHandlingKind::Transactional);
+
+        assert_eq!(report.outcomes[0], Outcome::RolledBack);
+        assert_eq!(workspace.get(&uri("file:///a")).unwrap().text, "b");
+        assert_eq!(workspace.get(&uri("file:///a/d")).unwrap().text, "d");
+    }
+}